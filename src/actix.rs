@@ -0,0 +1,141 @@
+//! # Actix
+//! Request bodies and responses in any of the supported formats for actix-web, enabled with the
+//! `actix-web` feature.
+//!
+//! `SimpleBody<T>` extracts `T` from the request body, decoded with the format and parameters of
+//! the `Content-Type` header, see `media_type` module documentation, using the `DecodeOptions` in
+//! the app data when there is one.
+//! `SimpleResponse<T>` encodes `T` with the format negotiated from the `Accept` header, see
+//! `negotiate` module documentation, JSON when the header is missing. Formats that can not encode
+//! `T`, like Avro without a registered schema or Toml for a value that is not a table, are skipped
+//! in favour of the next acceptable one.
+//!
+//! Errors are responded with their message, except for 500 Internal Server Error, whose details
+//! are logged with the `log` crate rather than sent to the client.
+//!
+//! | Failure                                          | Status                       |
+//! |--------------------------------------------------|------------------------------|
+//! | Missing or unsupported `Content-Type`            | 415 Unsupported Media Type   |
+//! | None of the formats in `Accept` encode the value | 406 Not Acceptable           |
+//! | The body could not be decoded                    | 400 Bad Request              |
+//!
+//! ```rust
+//! use actix_web::{web, App};
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::actix::{SimpleBody, SimpleResponse};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! async fn echo(body: SimpleBody<Foo>) -> SimpleResponse<Foo> {
+//!     SimpleResponse(body.into_inner())
+//! }
+//!
+//! let app = App::new().route("/echo", web::post().to(echo));
+//! ```
+
+use crate::{negotiate, ContentType, DecodeOptions, Decoded, Error, MediaType, SimpleDecoder};
+use actix_web::body::BoxBody;
+use actix_web::dev::Payload;
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, Responder, ResponseError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+
+/// Extracts `T` from a request body in the format of its `Content-Type`.
+#[derive(Debug)]
+pub struct SimpleBody<T>(pub T);
+
+impl<T> SimpleBody<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for SimpleBody<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SimpleBody<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for SimpleBody<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let media_type = media_type(req.headers());
+        let options = req.app_data::<DecodeOptions>().cloned().unwrap_or_default();
+        let body = Bytes::from_request(req, payload);
+        Box::pin(async move {
+            let media_type = media_type?;
+            let content_type = media_type.content_type()?;
+            let options = options.media_type(media_type);
+            let body = body.await?;
+            let decoded: Decoded<T> = body.as_ref().decode_with(content_type, &options)?;
+            Ok(SimpleBody(decoded.into()))
+        })
+    }
+}
+
+/// Responds with `T` in the format negotiated from the `Accept` header.
+#[derive(Debug)]
+pub struct SimpleResponse<T>(pub T);
+
+impl<T: Serialize> Responder for SimpleResponse<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let accept = req.headers().get(header::ACCEPT);
+        let accept = accept.map(|accept| accept.to_str().unwrap_or_default());
+        let accepted = ContentType::accepted(accept);
+        match negotiate::encode_accepted(&self.0, accept, accepted) {
+            Ok((content_type, encoded)) => HttpResponse::Ok()
+                .insert_header((header::CONTENT_TYPE, content_type.mime()))
+                .body(encoded.to_vec()),
+            Err(e) => e.error_response(),
+        }
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// The message of the error, for 500 Internal Server Error only logged as it is not about the
+    /// request.
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        let status = self.status_code();
+        match status {
+            StatusCode::INTERNAL_SERVER_ERROR => {
+                log::error!("Responding with {}: {}", status, self);
+                HttpResponse::build(status).body("Internal Server Error")
+            }
+            _ => HttpResponse::build(status).body(self.to_string()),
+        }
+    }
+}
+
+fn media_type(headers: &HeaderMap) -> crate::Result<MediaType> {
+    let value = headers
+        .get(header::CONTENT_TYPE)
+        .ok_or(Error::ContentTypeNotDetected)?
+        .to_str()
+        .map_err(|e| Error::UnknownContentTypeMatchFromStr(e.to_string()))?;
+    MediaType::parse(value)
+}
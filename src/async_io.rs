@@ -0,0 +1,184 @@
+//! # Async
+//! Async encoding and decoding over tokio's `AsyncWrite`/`AsyncRead`, enabled with the `tokio`
+//! feature.
+//!
+//! None of the backends are able to encode/decode asynchronously, so the value is always encoded
+//! into an internal buffer before it is written, and the reader is read until EOF before the data
+//! is decoded.
+//!
+//! `AsyncEncoder` and `AsyncDecoder` keeps their buffers between calls, making them cancellation
+//! safe: If an `encode` future is dropped before completing, the encoded value is still queued and
+//! written by the next call to `encode` or `flush`. If a `decode` future is dropped, the data read
+//! so far is kept and the next call to `decode` will continue where it left off.
+//! `encode_async` and `decode_async` are shorthands for a single value, and loses the buffer when
+//! cancelled.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::async_io::{decode_async, encode_async};
+//! use simple_serde::Decoded;
+//! use tokio::io::AsyncWriteExt;
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let (mut client, server) = tokio::io::duplex(64);
+//! let writer = tokio::spawn(async move {
+//!     encode_async(&Foo { bar: "foobar".to_string() }, &mut client, "json").await.unwrap();
+//!     client.shutdown().await.unwrap();
+//! });
+//! let decoded: Decoded<Foo> = decode_async(server, "json").await.unwrap();
+//! writer.await.unwrap();
+//! assert_eq!(Foo { bar: "foobar".to_string() }, decoded.into());
+//! # });
+//! ```
+
+use crate::{ContentType, Decoded, Error, Result, SimpleDecoder, SimpleEncoder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Encode `value` and write it to `writer`, flushing the writer once the value has been written.
+pub async fn encode_async<T, W, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    value: &T,
+    writer: W,
+    content_type: F,
+) -> Result<()>
+where
+    T: Serialize,
+    W: AsyncWrite + Unpin,
+{
+    AsyncEncoder::new(writer).encode(value, content_type).await
+}
+
+/// Read `reader` until EOF and decode the data read.
+pub async fn decode_async<T, R, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    reader: R,
+    content_type: F,
+) -> Result<Decoded<T>>
+where
+    T: DeserializeOwned,
+    R: AsyncRead + Unpin,
+{
+    AsyncDecoder::new(reader).decode(content_type).await
+}
+
+/// Cancellation safe encoder writing to an `AsyncWrite`.
+pub struct AsyncEncoder<W> {
+    writer: W,
+    pending: Vec<u8>,
+    written: usize,
+}
+
+impl<W> AsyncEncoder<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    pub fn new(writer: W) -> Self {
+        AsyncEncoder {
+            writer,
+            pending: Vec::new(),
+            written: 0,
+        }
+    }
+
+    /// Encode `value` and write it to the underlying writer. The value is queued as soon as the
+    /// future is first polled, if the future is cancelled the remaining data is written by the
+    /// next call to `encode` or `flush`.
+    pub async fn encode<T, F: TryInto<ContentType, Error = impl Into<Error>>>(
+        &mut self,
+        value: &T,
+        content_type: F,
+    ) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let encoded = value.encode(content_type)?;
+        self.pending.extend_from_slice(&encoded);
+        self.flush().await
+    }
+
+    /// Write any queued data and flush the underlying writer.
+    pub async fn flush(&mut self) -> Result<()> {
+        while self.written < self.pending.len() {
+            match self.writer.write(&self.pending[self.written..]).await? {
+                0 => return Err(std::io::Error::from(std::io::ErrorKind::WriteZero).into()),
+                n => self.written += n,
+            }
+        }
+        self.pending.clear();
+        self.written = 0;
+        self.writer.flush().await.map_err(Error::from)
+    }
+
+    /// Data that has been encoded but not yet written.
+    pub fn pending(&self) -> &[u8] {
+        &self.pending[self.written..]
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer, any data that has not been written is discarded.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Cancellation safe decoder reading from an `AsyncRead`.
+pub struct AsyncDecoder<R> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+impl<R> AsyncDecoder<R>
+where
+    R: AsyncRead + Unpin,
+{
+    pub fn new(reader: R) -> Self {
+        AsyncDecoder {
+            reader,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Read the underlying reader until EOF and decode the data read. If the future is cancelled,
+    /// the data read so far is kept for the next call to `decode`.
+    pub async fn decode<T, F: TryInto<ContentType, Error = impl Into<Error>>>(
+        &mut self,
+        content_type: F,
+    ) -> Result<Decoded<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let content_type: ContentType = content_type.try_into().map_err(|e| e.into())?;
+        while self.reader.read_buf(&mut self.buffer).await? != 0 {}
+        std::mem::take(&mut self.buffer).decode(content_type)
+    }
+
+    /// Data that has been read but not yet decoded.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the underlying reader, any data that has not been decoded is discarded.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
@@ -0,0 +1,221 @@
+//! # Avro
+//! Avro support is build on top of `avro-rs`, and unlike the other formats Avro needs a schema to be
+//! able to encode anything.
+//!
+//! The schema can either be given explicitly, using the functions in this module, or registered
+//! for a type using `register_schema`. When a schema has been registered for a type, the type can
+//! be encoded/decoded with `.encode("avro")` and `.decode("avro")` like any other format.
+//!
+//! `ContentType::Avro` reads and writes Avro Object Container Files, the `to_datum`/`from_datum`
+//! functions can be used for single datums without the container header.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::avro::{register_schema_with_codec, Codec, Schema};
+//! use simple_serde::{Decoded, SimpleDecoder, SimpleEncoder};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let schema = Schema::parse_str(
+//!     r#"{"type": "record", "name": "Foo", "fields": [{"name": "bar", "type": "string"}]}"#,
+//! )
+//! .unwrap();
+//! register_schema_with_codec::<Foo>(schema, Codec::Deflate);
+//!
+//! let encoded = Foo { bar: "foobar".to_string() }.encode("avro").unwrap();
+//! let decoded: Decoded<Foo> = encoded.decode().unwrap();
+//! assert_eq!(Foo { bar: "foobar".to_string() }, decoded.into());
+//! ```
+
+use crate::{Error, Result};
+use avro_rs::types::Value;
+use avro_rs::{Reader, Writer};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::type_name;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::{OnceLock, RwLock};
+
+pub use avro_rs::{Codec, Schema};
+
+/// A schema registered for a type, together with the codec used when writing container files.
+#[derive(Clone, Debug)]
+pub struct RegisteredSchema {
+    pub schema: Schema,
+    pub codec: Codec,
+}
+
+fn registry() -> &'static RwLock<HashMap<&'static str, RegisteredSchema>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<&'static str, RegisteredSchema>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register the schema used when encoding/decoding `T` as `ContentType::Avro`. Container files
+/// written with this schema will not be compressed.
+pub fn register_schema<T: ?Sized>(schema: Schema) {
+    register_schema_with_codec::<T>(schema, Codec::Null)
+}
+
+/// Register the schema and compression codec used when encoding/decoding `T` as
+/// `ContentType::Avro`. Registering a schema for the same type twice replaces the previous one.
+pub fn register_schema_with_codec<T: ?Sized>(schema: Schema, codec: Codec) {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(type_name::<T>(), RegisteredSchema { schema, codec });
+}
+
+/// Get the schema registered for `T`, if any.
+pub fn registered_schema<T: ?Sized>() -> Option<RegisteredSchema> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(type_name::<T>())
+        .cloned()
+}
+
+fn require_schema<T: ?Sized>() -> Result<RegisteredSchema> {
+    registered_schema::<T>()
+        .ok_or_else(|| Error::AvroSchemaNotRegistered(type_name::<T>().to_string()))
+}
+
+fn to_resolved_value<T: Serialize + ?Sized>(value: &T, schema: &Schema) -> Result<Value> {
+    avro_rs::to_value(value)
+        .and_then(|v| v.resolve(schema))
+        .map_err(Error::from)
+}
+
+/// Write an Object Container File holding `value` as its only record.
+pub fn to_container<T: Serialize + ?Sized>(
+    value: &T,
+    schema: &Schema,
+    codec: Codec,
+) -> Result<Vec<u8>> {
+    to_container_iter(std::iter::once(value), schema, codec)
+}
+
+/// Write an Object Container File holding every value of `values` as a record.
+pub fn to_container_iter<'a, I, T>(values: I, schema: &Schema, codec: Codec) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = &'a T>,
+    T: Serialize + ?Sized + 'a,
+{
+    write_container(Vec::new(), values, schema, codec)
+}
+
+/// Write an Object Container File holding every value of `values` as a record to `writer`, blocks
+/// are flushed to the writer as they fill up. The writer is handed back when the file is complete.
+pub fn write_container<'a, W, I, T>(
+    writer: W,
+    values: I,
+    schema: &Schema,
+    codec: Codec,
+) -> Result<W>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a T>,
+    T: Serialize + ?Sized + 'a,
+{
+    let mut writer = Writer::with_codec(schema, writer, codec);
+    for value in values {
+        writer.append(to_resolved_value(value, schema)?)?;
+    }
+    writer.into_inner().map_err(Error::from)
+}
+
+/// Read every record of an Object Container File. The schema embedded in the file is used as the
+/// writer schema, and schema resolution is performed when a `reader_schema` is given.
+pub fn from_container<T: DeserializeOwned>(
+    data: &[u8],
+    reader_schema: Option<&Schema>,
+) -> Result<Vec<T>> {
+    read_container(data, reader_schema)
+}
+
+/// Read every record of an Object Container File from `reader`, one block at a time.
+pub fn read_container<R: Read, T: DeserializeOwned>(
+    reader: R,
+    reader_schema: Option<&Schema>,
+) -> Result<Vec<T>> {
+    records(reader, reader_schema)?.collect()
+}
+
+fn records<'s, R: Read + 's, T: DeserializeOwned>(
+    reader: R,
+    reader_schema: Option<&'s Schema>,
+) -> Result<impl Iterator<Item = Result<T>> + 's> {
+    let reader = match reader_schema {
+        Some(schema) => Reader::with_schema(schema, reader)?,
+        None => Reader::new(reader)?,
+    };
+    Ok(reader.map(|value| {
+        value
+            .and_then(|v| avro_rs::from_value(&v))
+            .map_err(Error::from)
+    }))
+}
+
+/// Encode `value` as a single datum, without the Object Container File header.
+pub fn to_datum<T: Serialize + ?Sized>(value: &T, schema: &Schema) -> Result<Vec<u8>> {
+    to_resolved_value(value, schema)
+        .and_then(|v| avro_rs::to_avro_datum(schema, v).map_err(Error::from))
+}
+
+/// Decode a single datum written with `writer_schema`, schema resolution is performed when a
+/// `reader_schema` is given.
+pub fn from_datum<T: DeserializeOwned>(
+    mut data: &[u8],
+    writer_schema: &Schema,
+    reader_schema: Option<&Schema>,
+) -> Result<T> {
+    avro_rs::from_avro_datum(writer_schema, &mut data, reader_schema)
+        .and_then(|v| avro_rs::from_value(&v))
+        .map_err(Error::from)
+}
+
+/// Encode `value` as a single datum using the schema registered for `T`.
+pub fn to_registered_datum<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    require_schema::<T>().and_then(|registered| to_datum(value, &registered.schema))
+}
+
+/// Decode a single datum using the schema registered for `T` as writer schema.
+pub fn from_registered_datum<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    require_schema::<T>().and_then(|registered| from_datum(data, &registered.schema, None))
+}
+
+pub(crate) fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    encode_to_writer(Vec::new(), value)
+}
+
+pub(crate) fn encode_to_writer<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<W> {
+    require_schema::<T>().and_then(|registered| {
+        write_container(
+            writer,
+            std::iter::once(value),
+            &registered.schema,
+            registered.codec,
+        )
+    })
+}
+
+/// Decode `T` using the schema registered for `S`, which differs from `T` when `T` wraps the type
+/// the schema is registered for.
+pub(crate) fn decode<S: ?Sized, T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    decode_from_reader::<S, &[u8], T>(data)
+}
+
+pub(crate) fn decode_from_reader<S: ?Sized, R: Read, T: DeserializeOwned>(reader: R) -> Result<T> {
+    let registered = registered_schema::<S>();
+    let mut records = records::<R, T>(reader, registered.as_ref().map(|r| &r.schema))?;
+    // stop at the second record rather than decoding the rest of the container, so any count
+    // above one is reported as 2
+    match (records.next().transpose()?, records.next().transpose()?) {
+        (Some(record), None) => Ok(record),
+        (None, _) => Err(Error::AvroUnexpectedRecordCount(0)),
+        (Some(_), Some(_)) => Err(Error::AvroUnexpectedRecordCount(2)),
+    }
+}
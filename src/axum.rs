@@ -0,0 +1,161 @@
+//! # Axum
+//! Request bodies and responses in any of the supported formats for axum, enabled with the `axum`
+//! feature.
+//!
+//! `Simple<T>` extracts `T` from the request body, decoded with the format and parameters of the
+//! `Content-Type` header, using the `DecodeOptions` in the request extensions when there is one,
+//! e.g. added with `axum::Extension`. As a response `Simple<T>` encodes `T` with the format
+//! negotiated by the `negotiate` middleware from the `Accept` header, see `crate::negotiate` module
+//! documentation, JSON when the header is missing. Formats that can not encode `T`, like Avro
+//! without a registered schema or Toml for a value that is not a table, are skipped in favour of
+//! the next acceptable one. Responses outside the middleware fail with 500 Internal Server Error,
+//! rather than ignoring the `Accept` header of the request.
+//!
+//! Errors are responded with their message, except for 500 Internal Server Error, whose details
+//! are logged with the `log` crate rather than sent to the client.
+//!
+//! | Failure                                          | Status                       |
+//! |--------------------------------------------------|------------------------------|
+//! | Missing or unsupported `Content-Type`            | 415 Unsupported Media Type   |
+//! | None of the formats in `Accept` encode the value | 406 Not Acceptable           |
+//! | The body could not be decoded                    | 400 Bad Request              |
+//! | The route is not behind the `negotiate` layer    | 500 Internal Server Error    |
+//!
+//! ```rust
+//! use axum::routing::post;
+//! use axum::{middleware, Router};
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::axum::{negotiate, Simple};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! async fn echo(Simple(foo): Simple<Foo>) -> Simple<Foo> {
+//!     Simple(foo)
+//! }
+//!
+//! let app: Router = Router::new()
+//!     .route("/echo", post(echo))
+//!     .layer(middleware::from_fn(negotiate));
+//! ```
+
+use crate::{negotiate, ContentType, DecodeOptions, Decoded, Error, MediaType, SimpleDecoder};
+use ::axum::async_trait;
+use ::axum::body::Bytes;
+use ::axum::extract::{FromRequest, Request};
+use ::axum::http::header::{ACCEPT, CONTENT_TYPE};
+use ::axum::http::StatusCode;
+use ::axum::middleware::Next;
+use ::axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::ops::{Deref, DerefMut};
+
+tokio::task_local! {
+    /// The `Accept` header of the request, and the content types it accepts.
+    static NEGOTIATED: (Option<String>, Vec<ContentType>);
+}
+
+/// Extracts `T` from a request body in the format of its `Content-Type`, and responds with `T` in
+/// the format negotiated by `negotiate`.
+#[derive(Debug)]
+pub struct Simple<T>(pub T);
+
+impl<T> Simple<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Simple<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Simple<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[async_trait]
+impl<T, S> FromRequest<S> for Simple<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let media_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .ok_or(Error::ContentTypeNotDetected)
+            .and_then(MediaType::try_from)
+            .map_err(IntoResponse::into_response)?;
+        let content_type = media_type
+            .content_type()
+            .map_err(IntoResponse::into_response)?;
+        let options = req.extensions().get::<DecodeOptions>().cloned();
+        let options = options.unwrap_or_default().media_type(media_type);
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        let decoded: Decoded<T> = body
+            .as_ref()
+            .decode_with(content_type, &options)
+            .map_err(IntoResponse::into_response)?;
+        Ok(Simple(decoded.into()))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Simple<T> {
+    fn into_response(self) -> Response {
+        let (accept, accepted) = match NEGOTIATED.try_with(Clone::clone) {
+            Ok(negotiated) => negotiated,
+            Err(_) => {
+                let e = "responding with `Simple` requires the `negotiate` middleware";
+                return (StatusCode::INTERNAL_SERVER_ERROR, e).into_response();
+            }
+        };
+        match negotiate::encode_accepted(&self.0, accept.as_deref(), accepted) {
+            Ok((content_type, encoded)) => {
+                ([(CONTENT_TYPE, content_type.mime())], encoded.to_vec()).into_response()
+            }
+            Err(e) => e.into_response(),
+        }
+    }
+}
+
+/// The message of the error, for 500 Internal Server Error only logged as it is not about the
+/// request.
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        match status {
+            StatusCode::INTERNAL_SERVER_ERROR => {
+                log::error!("Responding with {}: {}", status, self);
+                (status, "Internal Server Error").into_response()
+            }
+            _ => (status, self.to_string()).into_response(),
+        }
+    }
+}
+
+/// Middleware negotiating the format of `Simple` responses from the `Accept` header, responding
+/// with 406 Not Acceptable without calling the handler when none of the formats are supported.
+pub async fn negotiate(req: Request, next: Next) -> Response {
+    let accept = req.headers().get(ACCEPT);
+    let accept = accept.map(|accept| accept.to_str().unwrap_or_default().to_string());
+    let accepted = ContentType::accepted(accept.as_deref());
+    match accepted.is_empty() {
+        true => Error::NotAcceptable(accept.unwrap_or_default()).into_response(),
+        false => NEGOTIATED.scope((accept, accepted), next.run(req)).await,
+    }
+}
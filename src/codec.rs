@@ -0,0 +1,216 @@
+//! # Codec
+//! Adding formats that are not built into simple serde, or overriding the settings of a built-in
+//! backend, without forking the crate.
+//!
+//! A format is added by implementing `Codec` and registering it, either in the global registry
+//! using `register`, or in a `CodecRegistry` of its own. A registered codec is looked up by its
+//! MIME type or any of its aliases, and is represented as `ContentType::Custom`, so it is
+//! encoded/decoded like any other `ContentType`.
+//!
+//! Names registered in the global registry are resolved before the built-in names when converting
+//! a `&str`/`String` into a `ContentType`, making it possible to replace a built-in format like
+//! `json`. A `ContentType` given directly, like `ContentType::Json`, always uses the built-in
+//! backend.
+//!
+//! The value being encoded and the deserializer used for decoding are type erased using
+//! `erased-serde`, errors from a custom codec are returned as `Error::CodecFailure`, wrapped in
+//! `Error::EncodeFailure` or `Error::DecodeFailure`.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::codec::{self, Codec, DecodeVisitor};
+//! use simple_serde::prelude::erased_serde;
+//! use simple_serde::{Decoded, SimpleDecoder, SimpleEncoder, TryToString};
+//!
+//! struct PrettyJson;
+//!
+//! impl Codec for PrettyJson {
+//!     fn mime(&self) -> &str {
+//!         "application/x-pretty-json"
+//!     }
+//!
+//!     fn encode(&self, value: &dyn erased_serde::Serialize) -> simple_serde::Result<Vec<u8>> {
+//!         serde_json::to_vec_pretty(value).map_err(Into::into)
+//!     }
+//!
+//!     fn decode(&self, data: &[u8], visit: DecodeVisitor) -> simple_serde::Result<()> {
+//!         let mut de = serde_json::Deserializer::from_slice(data);
+//!         visit(&mut <dyn erased_serde::Deserializer>::erase(&mut de))
+//!     }
+//! }
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! codec::register(PrettyJson, &["pretty-json"]);
+//!
+//! let encoded = Foo { bar: "foobar".to_string() }.encode("pretty-json").unwrap();
+//! assert_eq!("{\n  \"bar\": \"foobar\"\n}", encoded.try_to_string().unwrap());
+//! let decoded: Decoded<Foo> = encoded.as_slice().decode("application/x-pretty-json").unwrap();
+//! assert_eq!(Foo { bar: "foobar".to_string() }, decoded.into());
+//! ```
+
+use crate::{ContentType, Error, MediaType, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Hands a type erased deserializer to the decoder, see `Codec::decode`.
+pub type DecodeVisitor<'a> =
+    &'a mut dyn for<'de> FnMut(&mut dyn erased_serde::Deserializer<'de>) -> Result<()>;
+
+/// A format that can be registered in a `CodecRegistry`.
+pub trait Codec: Send + Sync {
+    /// The canonical MIME type of the format.
+    fn mime(&self) -> &str;
+
+    /// Encode the type erased `value`.
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> Result<Vec<u8>>;
+
+    /// Decode `data` by handing a deserializer reading `data` to `visit`, the result of `visit`
+    /// must be returned.
+    fn decode(&self, data: &[u8], visit: DecodeVisitor) -> Result<()>;
+
+    /// Decode `data` sent as `media_type`, giving access to parameters like `charset` or
+    /// `version`. Called instead of `decode` when `DecodeOptions::media_type` is set, defaults to
+    /// ignoring the parameters.
+    fn decode_media_type(
+        &self,
+        data: &[u8],
+        media_type: &MediaType,
+        visit: DecodeVisitor,
+    ) -> Result<()> {
+        let _ = media_type;
+        self.decode(data, visit)
+    }
+}
+
+impl PartialEq for dyn Codec {
+    fn eq(&self, other: &Self) -> bool {
+        self.mime() == other.mime()
+    }
+}
+
+impl Eq for dyn Codec {}
+
+impl fmt::Debug for dyn Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.mime())
+    }
+}
+
+/// Maps MIME types and aliases to formats. The built-in formats are always resolved, formats
+/// registered in the registry take precedence over the built-in ones.
+#[derive(Clone, Default, Debug)]
+pub struct CodecRegistry {
+    formats: HashMap<String, ContentType>,
+}
+
+impl CodecRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `codec` under its MIME type and `aliases`, replacing any format previously
+    /// registered under the same names. Names are case insensitive.
+    pub fn register<C: Codec + 'static>(&mut self, codec: C, aliases: &[&str]) -> ContentType {
+        let codec: Arc<dyn Codec> = Arc::new(codec);
+        let content_type = ContentType::Custom(codec.clone());
+        self.alias(codec.mime(), content_type.clone());
+        for alias in aliases {
+            self.alias(alias, content_type.clone());
+        }
+        content_type
+    }
+
+    /// Register an additional name for `content_type`, which can be either a built-in or a custom
+    /// format.
+    pub fn alias(&mut self, alias: &str, content_type: ContentType) {
+        self.formats.insert(alias.to_lowercase(), content_type);
+    }
+
+    /// Look up the format registered under `name`, falling back to the built-in formats.
+    pub fn lookup(&self, name: &str) -> Result<ContentType> {
+        match self.get(name) {
+            Some(content_type) => Ok(content_type),
+            None => ContentType::builtin(name),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<ContentType> {
+        self.formats.get(&name.to_lowercase()).cloned()
+    }
+}
+
+fn registry() -> &'static RwLock<CodecRegistry> {
+    static REGISTRY: OnceLock<RwLock<CodecRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register `codec` in the global registry, see `CodecRegistry::register`.
+pub fn register<C: Codec + 'static>(codec: C, aliases: &[&str]) -> ContentType {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register(codec, aliases)
+}
+
+/// Register an additional name for `content_type` in the global registry.
+pub fn register_alias(alias: &str, content_type: ContentType) {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .alias(alias, content_type)
+}
+
+/// A copy of the global registry.
+pub fn global() -> CodecRegistry {
+    registry().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub(crate) fn registered(name: &str) -> Option<ContentType> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(name)
+}
+
+pub(crate) fn encode<T: serde::Serialize>(codec: &dyn Codec, value: &T) -> Result<Vec<u8>> {
+    codec.encode(value)
+}
+
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(
+    codec: &dyn Codec,
+    data: &[u8],
+    media_type: Option<&MediaType>,
+) -> Result<T> {
+    visit(codec, data, media_type, |de| {
+        erased_serde::deserialize::<T>(de).map_err(Error::from)
+    })
+}
+
+/// Hand the deserializer of `codec` to `f`, returning the result of `f`.
+pub(crate) fn visit<R>(
+    codec: &dyn Codec,
+    data: &[u8],
+    media_type: Option<&MediaType>,
+    mut f: impl for<'de> FnMut(&mut dyn erased_serde::Deserializer<'de>) -> Result<R>,
+) -> Result<R> {
+    let mut result = None;
+    let visit: DecodeVisitor = &mut |de| {
+        result = Some(f(de)?);
+        Ok(())
+    };
+    match media_type {
+        Some(media_type) => codec.decode_media_type(data, media_type, visit)?,
+        None => codec.decode(data, visit)?,
+    }
+    result.ok_or_else(|| {
+        Error::CodecFailure(Arc::new(serde::de::Error::custom(format!(
+            "{} did not decode a value",
+            codec.mime()
+        ))))
+    })
+}
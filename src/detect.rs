@@ -0,0 +1,194 @@
+//! # Detect
+//! Content sniffing for payloads where the content type is missing or can not be trusted.
+//!
+//! Binary formats are recognized by their magic bytes and structure:
+//! - Avro: the `Obj\x01` Object Container File header.
+//! - Bson: the little endian length prefix matching the payload, and the trailing `\x00`.
+//! - Cbor: the self-describe tag `0xd9d9f7`, otherwise a top level map/array decoding the complete
+//!   payload.
+//! - FlexBuffers: the root type and byte width trailer.
+//! - MessagePack: a top level map/array decoding the complete payload.
+//! - Pickle: the `PROTO` opcode for protocol 2 and up, and the trailing `STOP` opcode.
+//!
+//! Text formats are recognized by how the payload starts, and by whether the backend is able to
+//! parse it. Many text payloads are valid in more than one format, JSON is also valid JSON5, YAML
+//! and RON, which is why `ContentType::detect_all` ranks every candidate by its `Confidence`.
+//!
+//! Postcard is not self-describing and is never detected.
+//!
+//! ```rust
+//! use simple_serde::{Confidence, ContentType};
+//!
+//! assert_eq!(
+//!     Some((ContentType::Json, Confidence::High)),
+//!     ContentType::detect(br#"{"bar": "foobar"}"#)
+//! );
+//! assert_eq!(
+//!     vec![
+//!         (ContentType::Json, Confidence::High),
+//!         (ContentType::Json5, Confidence::Medium),
+//!         (ContentType::Yaml, Confidence::Low),
+//!         (ContentType::Ron, Confidence::Low),
+//!     ],
+//!     ContentType::detect_all(br#"{"bar": "foobar"}"#)
+//! );
+//! ```
+
+use crate::ContentType;
+use serde::de::IgnoredAny;
+use std::io::Cursor;
+
+/// How certain the detection is of a candidate, ordered from `Low` to `Certain`.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Confidence {
+    /// The payload parses, but is very likely to be another format.
+    Low,
+    /// The payload parses, but is also valid in other formats.
+    Medium,
+    /// The payload parses and has traits that are specific to the format.
+    High,
+    /// The payload carries a magic number identifying the format.
+    Certain,
+}
+
+impl ContentType {
+    /// The most likely content type of `data`, if any format matches.
+    pub fn detect(data: &[u8]) -> Option<(ContentType, Confidence)> {
+        Self::detect_all(data).into_iter().next()
+    }
+
+    /// Every content type matching `data`, ranked from the most to the least likely.
+    pub fn detect_all(data: &[u8]) -> Vec<(ContentType, Confidence)> {
+        let mut candidates = detect_binary(data);
+        if let Ok(text) = std::str::from_utf8(data) {
+            candidates.extend(detect_text(text.trim()));
+        }
+        candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+        candidates
+    }
+}
+
+fn detect_binary(data: &[u8]) -> Vec<(ContentType, Confidence)> {
+    let mut candidates = Vec::new();
+    if data.starts_with(b"Obj\x01") {
+        candidates.push((ContentType::Avro, Confidence::Certain));
+    }
+    if is_bson(data) {
+        candidates.push((ContentType::Bson, Confidence::High));
+    }
+    if data.starts_with(&[0xd9, 0xd9, 0xf7]) {
+        candidates.push((ContentType::Cbor, Confidence::Certain));
+    } else if matches!(data.first(), Some(0x80..=0xbf))
+        && serde_cbor::from_slice::<IgnoredAny>(data).is_ok()
+    {
+        candidates.push((ContentType::Cbor, Confidence::Medium));
+    }
+    if is_pickle(data) {
+        candidates.push((ContentType::Pickle, Confidence::High));
+    } else if matches!(data.first(), Some(0x80..=0x9f | 0xdc..=0xdf)) && is_message_pack(data) {
+        candidates.push((ContentType::MessagePack, Confidence::Medium));
+    }
+    if is_flexbuffers(data) {
+        candidates.push((ContentType::FlexBuffers, Confidence::Medium));
+    }
+    candidates
+}
+
+fn is_bson(data: &[u8]) -> bool {
+    data.len() >= 5
+        && data.last() == Some(&0)
+        && u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize == data.len()
+        && bson::from_slice::<bson::Document>(data).is_ok()
+}
+
+fn is_pickle(data: &[u8]) -> bool {
+    matches!(data, [0x80, 2..=5, .., b'.'])
+}
+
+fn is_message_pack(data: &[u8]) -> bool {
+    let mut de = rmp_serde::Deserializer::new(Cursor::new(data));
+    serde::Deserialize::deserialize(&mut de)
+        .map(|_: IgnoredAny| de.position() as usize == data.len())
+        .unwrap_or(false)
+}
+
+fn is_flexbuffers(data: &[u8]) -> bool {
+    match data {
+        [.., _, width @ (1 | 2 | 4 | 8)] if data.len() > *width as usize + 2 => {
+            flexbuffers::Reader::get_root(data)
+                .map(|root| root.flexbuffer_type().is_map() || root.flexbuffer_type().is_vector())
+                .unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+fn detect_text(text: &str) -> Vec<(ContentType, Confidence)> {
+    let mut candidates = Vec::new();
+    let first = match text.chars().next() {
+        Some(first) => first,
+        None => return candidates,
+    };
+    let structured = first == '{' || first == '[';
+    let json = serde_json::from_str::<IgnoredAny>(text).is_ok();
+    if json {
+        candidates.push((ContentType::Json, Confidence::High));
+    }
+    if structured && json5::from_str::<IgnoredAny>(text).is_ok() {
+        let confidence = match json {
+            true => Confidence::Medium,
+            false => Confidence::High,
+        };
+        candidates.push((ContentType::Json5, confidence));
+    }
+    if let Ok(yaml) = serde_yaml::from_str::<serde_yaml::Value>(text) {
+        let marked = text.starts_with("---") || text.starts_with("%YAML");
+        let confidence = match (marked, structured) {
+            (true, _) => Some(Confidence::High),
+            (false, true) => Some(Confidence::Low),
+            (false, false) if yaml.is_mapping() || yaml.is_sequence() => Some(Confidence::Medium),
+            (false, false) => None,
+        };
+        if let Some(confidence) = confidence {
+            candidates.push((ContentType::Yaml, confidence));
+        }
+    }
+    if !json && toml::from_str::<toml::Value>(text).is_ok() {
+        candidates.push((ContentType::Toml, Confidence::High));
+    }
+    if ron::from_str::<IgnoredAny>(text).is_ok() {
+        let confidence = match first == '(' || is_ron_struct(text) {
+            true => Confidence::High,
+            false => Confidence::Low,
+        };
+        candidates.push((ContentType::Ron, confidence));
+    }
+    if first == '(' && serde_lexpr::from_str::<IgnoredAny>(text).is_ok() {
+        candidates.push((ContentType::Lexpr, Confidence::Medium));
+    }
+    if text.contains('=') && text.bytes().all(is_url_byte) {
+        candidates.push((ContentType::Url, Confidence::Medium));
+    }
+    #[cfg(feature = "accept-limited-xml-serialize")]
+    if text.starts_with("<?xml") {
+        candidates.push((ContentType::Xml, Confidence::Certain));
+    } else if first == '<' && text.ends_with('>') {
+        candidates.push((ContentType::Xml, Confidence::Medium));
+    }
+    candidates
+}
+
+fn is_ron_struct(text: &str) -> bool {
+    text.find('(')
+        .map(|i| {
+            i > 0
+                && text[..i]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+        .unwrap_or(false)
+}
+
+fn is_url_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"%&=+-_.~[]*".contains(&b)
+}
@@ -0,0 +1,89 @@
+//! # File
+//! Reading and writing files in the format given by their extension.
+//!
+//! | Extension           | Format       |
+//! |---------------------|--------------|
+//! | `.avro`             | Avro         |
+//! | `.bson`             | Bson         |
+//! | `.cbor`             | Cbor         |
+//! | `.flexbuffers`      | FlexBuffers  |
+//! | `.json`             | Json         |
+//! | `.json5`            | Json5        |
+//! | `.jsonl`, `.ndjson` | JsonLines    |
+//! | `.sexp`, `.lexpr`   | Lexpr        |
+//! | `.msgpack`, `.mpk`  | MessagePack  |
+//! | `.pkl`, `.pickle`   | Pickle       |
+//! | `.postcard`         | Postcard     |
+//! | `.ron`              | Ron          |
+//! | `.toml`             | Toml         |
+//! | `.yaml`, `.yml`     | Yaml         |
+//! | `.xml`              | Xml          |
+//!
+//! Extensions are case insensitive, `.xml` needs the `accept-limited-xml-serialize` feature. A
+//! path without one of the extensions fails with `Error::UnknownExtension`.
+//!
+//! `save` writes to a temporary file next to `path` and renames it over `path` once everything has
+//! been written, so readers see either the old or the new content, never a partially written file.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::{load, save, Decoded};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let path = std::env::temp_dir().join(format!("simple-serde-doc-{}.yml", std::process::id()));
+//! save(&path, &Foo { bar: "foobar".to_string() }).unwrap();
+//! assert_eq!("bar: foobar\n", std::fs::read_to_string(&path).unwrap());
+//!
+//! let foo: Decoded<Foo> = load(&path).unwrap();
+//! assert_eq!(Foo { bar: "foobar".to_string() }, foo.into());
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use crate::{ContentType, Decoded, Error, Result, SimpleDecoder, SimpleEncoder};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Decode the file at `path` with the format of its extension.
+pub fn load<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<Decoded<T>> {
+    let path = path.as_ref();
+    let content_type = from_path(path)?;
+    fs::read(path)?.decode(content_type)
+}
+
+/// Encode `value` with the format of the extension of `path`, atomically replacing the file.
+pub fn save<T: Serialize, P: AsRef<Path>>(path: P, value: &T) -> Result<()> {
+    let path = path.as_ref();
+    let encoded = value.encode(from_path(path)?)?;
+    let temporary = temporary(path);
+    let written = write(&temporary, &encoded).and_then(|_| fs::rename(&temporary, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    written.map_err(Error::from)
+}
+
+fn from_path(path: &Path) -> Result<ContentType> {
+    ContentType::from_path(path).ok_or_else(|| Error::UnknownExtension(path.display().to_string()))
+}
+
+/// A path in the directory of `path`, unique within the process.
+fn temporary(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), count))
+}
+
+fn write(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
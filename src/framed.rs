@@ -0,0 +1,306 @@
+//! # Framed
+//! A tokio-util codec sending values of any `ContentType` as frames over a byte stream, enabled
+//! with the `tokio-util` feature.
+//!
+//! `SimpleCodec<T>` implements `Encoder<T>` and `Decoder` with `Item = T`, so it works with
+//! `Framed`, `FramedRead` and `FramedWrite` over TCP, Unix sockets or anything else implementing
+//! `AsyncRead`/`AsyncWrite`. How the frames are delimited is set with `Framing`:
+//!
+//! | Framing     | Frame                                                               |
+//! |-------------|---------------------------------------------------------------------|
+//! | `LengthU32` | The length as a big-endian `u32`, followed by the encoded value     |
+//! | `Varint`    | The length as an unsigned LEB128 varint, followed by the value      |
+//! | `Newline`   | The encoded value followed by `\n`, only for Json and JsonLines     |
+//! | `Cobs`      | The COBS encoded value followed by a zero byte                      |
+//!
+//! `LengthU32` is the default framing of tokio-util's `LengthDelimitedCodec`. Blank lines are
+//! skipped with `Newline`, and empty frames with `Cobs`.
+//!
+//! Frames larger than `max_frame_size`, not counting the length or the delimiter, fail with
+//! `Error::LimitExceeded` without buffering the frame. The limit defaults to 8 MiB.
+//!
+//! ```rust
+//! use bytes::BytesMut;
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::framed::{Framing, SimpleCodec};
+//! use tokio_util::codec::{Decoder, Encoder};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let mut codec = SimpleCodec::<Foo>::new("json", Framing::LengthU32).unwrap();
+//! let mut buffer = BytesMut::new();
+//! codec.encode(Foo { bar: "foobar".to_string() }, &mut buffer).unwrap();
+//! assert_eq!(&b"\0\0\0\x10{\"bar\":\"foobar\"}"[..], &buffer[..]);
+//!
+//! let decoded = codec.decode(&mut buffer).unwrap();
+//! assert_eq!(Some(Foo { bar: "foobar".to_string() }), decoded);
+//! assert!(buffer.is_empty());
+//! ```
+
+use crate::{ContentType, Decoded, Error, LimitKind, Result, SimpleDecoder, SimpleEncoder};
+use bytes::{Buf, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt;
+use std::marker::PhantomData;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The default of `SimpleCodec::max_frame_size`.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+/// How frames are delimited, see the module documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    LengthU32,
+    Varint,
+    Newline,
+    Cobs,
+}
+
+/// Encodes and decodes values of `T` as frames, see the module documentation.
+pub struct SimpleCodec<T> {
+    content_type: ContentType,
+    framing: Framing,
+    max_frame_size: usize,
+    /// Where to continue looking for the delimiter, so a partial frame is not scanned again when
+    /// more data arrives.
+    next_index: usize,
+    marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T> SimpleCodec<T> {
+    /// Fails with `Error::UnsupportedOption` for `Framing::Newline` with a format that might
+    /// encode a value on more than one line.
+    pub fn new<F: TryInto<ContentType, Error = impl Into<Error>>>(
+        content_type: F,
+        framing: Framing,
+    ) -> Result<Self> {
+        let content_type = content_type.try_into().map_err(|e| e.into())?;
+        match (framing, &content_type) {
+            (Framing::Newline, ContentType::Json | ContentType::JsonLines) => {}
+            (Framing::Newline, _) => {
+                let option = "newline framing".to_string();
+                return Err(Error::UnsupportedOption(content_type, option));
+            }
+            _ => {}
+        }
+        Ok(SimpleCodec {
+            content_type,
+            framing,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            next_index: 0,
+            marker: PhantomData,
+        })
+    }
+
+    /// The largest frame encoded or decoded, at most `u32::MAX`.
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size.min(u32::MAX as usize);
+        self
+    }
+
+    pub fn content_type(&self) -> &ContentType {
+        &self.content_type
+    }
+
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    fn check(&self, length: usize) -> Result<()> {
+        match length > self.max_frame_size {
+            true => Err(Error::LimitExceeded {
+                kind: LimitKind::FrameBytes,
+                limit: self.max_frame_size,
+            }),
+            false => Ok(()),
+        }
+    }
+
+    /// Split the next frame of a length prefixed framing off `src`.
+    fn length_prefixed(&self, src: &mut BytesMut) -> Result<Option<BytesMut>> {
+        let prefix = match self.framing {
+            Framing::LengthU32 => src
+                .get(..4)
+                .map(|prefix| (u32::from_be_bytes(prefix.try_into().unwrap()), 4)),
+            _ => varint(src)?,
+        };
+        let (length, prefix) = match prefix {
+            Some((length, prefix)) => (length as usize, prefix),
+            None => return Ok(None),
+        };
+        self.check(length)?;
+        if src.len() < prefix + length {
+            src.reserve(prefix + length - src.len());
+            return Ok(None);
+        }
+        src.advance(prefix);
+        Ok(Some(src.split_to(length)))
+    }
+
+    /// Split the next frame that is not blank off `src`, without its delimiter.
+    fn delimited(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>> {
+        let delimiter = match self.framing {
+            Framing::Newline => b'\n',
+            _ => 0,
+        };
+        loop {
+            let start = self.next_index.min(src.len());
+            let end = match src[start..].iter().position(|byte| *byte == delimiter) {
+                Some(end) => start + end,
+                None => {
+                    self.next_index = src.len();
+                    self.check(src.len())?;
+                    return Ok(None);
+                }
+            };
+            self.next_index = 0;
+            let mut frame = src.split_to(end + 1);
+            frame.truncate(end);
+            let blank = match self.framing {
+                Framing::Newline => frame.iter().all(u8::is_ascii_whitespace),
+                _ => frame.is_empty(),
+            };
+            if !blank {
+                self.check(frame.len())?;
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    fn decode_frame(&self, frame: &[u8]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let decoded: Result<Decoded<T>> = match self.framing {
+            Framing::Cobs => cobs::decode_vec(frame)
+                .map_err(|_| {
+                    let e = "invalid COBS encoding".to_string();
+                    Error::InvalidFrame(self.content_type.clone(), e)
+                })
+                .and_then(|frame| frame.decode(&self.content_type)),
+            _ => frame.decode(&self.content_type),
+        };
+        decoded.map(Decoded::into)
+    }
+}
+
+/// Read an unsigned LEB128 varint of at most 32 bits, and the number of bytes it takes up.
+fn varint(src: &[u8]) -> Result<Option<(u32, usize)>> {
+    let mut value = 0u64;
+    for (i, byte) in src.iter().take(5).enumerate() {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return match u32::try_from(value) {
+                Ok(value) => Ok(Some((value, i + 1))),
+                Err(_) => Err(Error::LimitExceeded {
+                    kind: LimitKind::FrameBytes,
+                    limit: u32::MAX as usize,
+                }),
+            };
+        }
+    }
+    match src.len() < 5 {
+        true => Ok(None),
+        false => Err(Error::LimitExceeded {
+            kind: LimitKind::FrameBytes,
+            limit: u32::MAX as usize,
+        }),
+    }
+}
+
+impl<T> Clone for SimpleCodec<T> {
+    fn clone(&self) -> Self {
+        SimpleCodec {
+            content_type: self.content_type.clone(),
+            framing: self.framing,
+            max_frame_size: self.max_frame_size,
+            next_index: self.next_index,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for SimpleCodec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimpleCodec")
+            .field("content_type", &self.content_type)
+            .field("framing", &self.framing)
+            .field("max_frame_size", &self.max_frame_size)
+            .finish()
+    }
+}
+
+impl<T: Serialize> Encoder<T> for SimpleCodec<T> {
+    type Error = Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
+        let mut frame = item.encode(&self.content_type)?.into_inner();
+        match self.framing {
+            Framing::Cobs => frame = cobs::encode_vec(&frame),
+            Framing::Newline if frame.last() == Some(&b'\n') => {
+                frame.pop();
+            }
+            _ => {}
+        }
+        self.check(frame.len())?;
+        match self.framing {
+            Framing::LengthU32 => dst.extend_from_slice(&(frame.len() as u32).to_be_bytes()),
+            Framing::Varint => {
+                let mut length = frame.len();
+                while length >= 0x80 {
+                    dst.extend_from_slice(&[(length as u8 & 0x7f) | 0x80]);
+                    length >>= 7;
+                }
+                dst.extend_from_slice(&[length as u8]);
+            }
+            Framing::Newline | Framing::Cobs => {}
+        }
+        dst.extend_from_slice(&frame);
+        match self.framing {
+            Framing::Newline => dst.extend_from_slice(b"\n"),
+            Framing::Cobs => dst.extend_from_slice(&[0]),
+            Framing::LengthU32 | Framing::Varint => {}
+        }
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for SimpleCodec<T> {
+    type Item = T;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        let frame = match self.framing {
+            Framing::LengthU32 | Framing::Varint => self.length_prefixed(src)?,
+            Framing::Newline | Framing::Cobs => self.delimited(src)?,
+        };
+        frame.map(|frame| self.decode_frame(&frame)).transpose()
+    }
+
+    /// A last line without a newline is decoded with `Framing::Newline`, any other data left
+    /// fails.
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        if let Some(item) = self.decode(src)? {
+            return Ok(Some(item));
+        }
+        self.next_index = 0;
+        if src.iter().all(u8::is_ascii_whitespace) && self.framing == Framing::Newline {
+            src.clear();
+        }
+        match (src.is_empty(), self.framing) {
+            (true, _) => Ok(None),
+            (false, Framing::Newline) => {
+                let frame = src.split();
+                self.decode_frame(&frame).map(Some)
+            }
+            (false, _) => {
+                let e = std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "incomplete frame");
+                Err(Error::from(e))
+            }
+        }
+    }
+}
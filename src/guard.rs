@@ -0,0 +1,657 @@
+//! Wraps the deserializer of the backend, enforcing `Limits` and tracking the path of the value
+//! being decoded, see the `limits` and `path` modules.
+//!
+//! The wrapper is injected by decoding into `Guarded<T>`, which works for every backend, including
+//! the ones that only take a `T: Deserialize`. The limits and the outcome are passed through
+//! thread locals, as `Deserialize::deserialize` takes no other arguments.
+
+use crate::path::DecodeError;
+use crate::{ContentType, Error, ErrorKind, LimitKind, Limits, Result};
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::Deserialize;
+use std::any::type_name;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Write};
+
+#[derive(Default)]
+struct Outcome {
+    exceeded: Option<(LimitKind, usize)>,
+    path: Option<String>,
+}
+
+thread_local! {
+    static ACTIVE: Cell<Limits> = Cell::new(Limits::default());
+    static OUTCOME: RefCell<Outcome> = RefCell::new(Outcome::default());
+}
+
+/// Decode with `decode`, enforcing `limits` and turning errors into `Error::DecodeFailure` for `T`.
+/// `decode` must decode into `Guarded<T>`.
+pub(crate) fn decode<T, F>(content_type: &ContentType, limits: &Limits, decode: F) -> Result<T>
+where
+    F: FnOnce() -> Result<Guarded<T>>,
+{
+    // restored afterwards, a custom codec may decode other values while decoding
+    let active = ACTIVE.with(|active| active.replace(*limits));
+    let previous = OUTCOME.with(|outcome| outcome.take());
+    let result = decode();
+    ACTIVE.with(|cell| cell.set(active));
+    let outcome = OUTCOME.with(|outcome| outcome.replace(previous));
+    match (result, outcome.exceeded) {
+        (_, Some((kind, limit))) => Err(Error::LimitExceeded { kind, limit }),
+        (Ok(guarded), None) => Ok(guarded.0),
+        // a nested decode already carries its path
+        (Err(error @ Error::DecodeFailure(_)), None) => Err(error),
+        // not about the input, so there is no path to report
+        (Err(error), None)
+            if matches!(
+                error.kind(),
+                ErrorKind::Unsupported | ErrorKind::UnsupportedContentType
+            ) =>
+        {
+            Err(error)
+        }
+        (Err(error), None) => Err(Error::DecodeFailure(Box::new(DecodeError::new(
+            content_type.clone(),
+            type_name::<T>(),
+            outcome.path,
+            error,
+        )))),
+    }
+}
+
+/// Deserializes `T` through `GuardedDeserializer`, using the limits of the current `decode`.
+pub(crate) struct Guarded<T>(pub(crate) T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Guarded<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let state = State {
+            limits: ACTIVE.with(|active| active.get()),
+            depth: Cell::new(0),
+            allocated: Cell::new(0),
+            path: RefCell::new(Vec::new()),
+            key: RefCell::new(None),
+        };
+        T::deserialize(GuardedDeserializer::new(deserializer, &state, None)).map(Guarded)
+    }
+}
+
+/// A segment of the path, integer keys are kept as `Index` until they are resolved to a field or
+/// variant name, see `Segment::resolve`. Keys naming a field or variant borrow the name, so decoding
+/// a struct allocates nothing for the path unless it fails.
+/// The field names of a struct or the variant names of an enum.
+type Names = &'static [&'static str];
+
+#[derive(Clone)]
+enum Segment {
+    Index(usize),
+    Name(Cow<'static, str>),
+    Unknown,
+}
+
+impl Segment {
+    /// The segment of a sequence item, named when the sequence is a struct.
+    fn item(index: usize, names: Option<Names>) -> Segment {
+        match names.and_then(|names| names.get(index)) {
+            Some(name) => Segment::Name(Cow::Borrowed(name)),
+            None => Segment::Index(index),
+        }
+    }
+
+    /// The segment of a string key, borrowed from `names` when it is one of them.
+    fn name(key: &impl AsRef<[u8]>, names: Names) -> Segment {
+        let key = key.as_ref();
+        match names.iter().find(|name| name.as_bytes() == key) {
+            Some(name) => Segment::Name(Cow::Borrowed(name)),
+            None => Segment::Name(Cow::Owned(String::from_utf8_lossy(key).into_owned())),
+        }
+    }
+
+    fn resolve(self, names: Option<Names>) -> Segment {
+        match self {
+            Segment::Index(index) => match names.and_then(|names| names.get(index)) {
+                Some(name) => Segment::Name(Cow::Borrowed(name)),
+                None => Segment::Name(Cow::Owned(index.to_string())),
+            },
+            segment => segment,
+        }
+    }
+}
+
+struct State {
+    limits: Limits,
+    depth: Cell<usize>,
+    allocated: Cell<usize>,
+    path: RefCell<Vec<Segment>>,
+    key: RefCell<Option<Segment>>,
+}
+
+impl State {
+    fn exceeded<E: de::Error>(kind: LimitKind, limit: usize) -> E {
+        OUTCOME.with(|outcome| outcome.borrow_mut().exceeded = Some((kind, limit)));
+        E::custom(format!("{} limit of {} exceeded", kind, limit))
+    }
+
+    fn check<E: de::Error>(
+        kind: LimitKind,
+        limit: Option<usize>,
+        value: usize,
+    ) -> std::result::Result<(), E> {
+        match limit {
+            Some(limit) if value > limit => Err(Self::exceeded(kind, limit)),
+            _ => Ok(()),
+        }
+    }
+
+    fn enter<E: de::Error>(&self, length: Option<usize>) -> std::result::Result<(), E> {
+        self.depth.set(self.depth.get() + 1);
+        Self::check(LimitKind::Depth, self.limits.depth, self.depth.get())?;
+        Self::check(
+            LimitKind::CollectionLength,
+            self.limits.collection_length,
+            length.unwrap_or(0),
+        )
+    }
+
+    fn leave(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+
+    fn item<E: de::Error>(&self, count: usize) -> std::result::Result<(), E> {
+        Self::check(
+            LimitKind::CollectionLength,
+            self.limits.collection_length,
+            count,
+        )
+    }
+
+    fn string<E: de::Error>(&self, length: usize) -> std::result::Result<(), E> {
+        Self::check(LimitKind::StringLength, self.limits.string_length, length)?;
+        self.allocated
+            .set(self.allocated.get().saturating_add(length));
+        Self::check(
+            LimitKind::TotalAllocation,
+            self.limits.total_allocation,
+            self.allocated.get(),
+        )
+    }
+
+    fn capture(&self, key: Segment) {
+        *self.key.borrow_mut() = Some(key);
+    }
+
+    fn captured(&self, names: Option<Names>) -> Segment {
+        match self.key.borrow_mut().take() {
+            Some(key) => key.resolve(names),
+            None => Segment::Unknown,
+        }
+    }
+
+    /// Run `f` with `segment` added to the path. The first failure records the path, as it is the
+    /// innermost one.
+    fn scoped<R, E>(
+        &self,
+        segment: Segment,
+        f: impl FnOnce() -> std::result::Result<R, E>,
+    ) -> std::result::Result<R, E> {
+        self.path.borrow_mut().push(segment);
+        let result = f();
+        if result.is_err() {
+            OUTCOME.with(|outcome| {
+                let mut outcome = outcome.borrow_mut();
+                if outcome.path.is_none() {
+                    outcome.path = Some(self.format_path());
+                }
+            });
+        }
+        self.path.borrow_mut().pop();
+        result
+    }
+
+    /// The path in jq style, `.` for the root, `.items[3].price` or `.[0]`. Names that are not
+    /// identifiers are quoted, `."a.b"`.
+    fn format_path(&self) -> String {
+        let mut formatted = String::new();
+        for segment in self.path.borrow().iter() {
+            let _ = match segment {
+                Segment::Index(index) if formatted.is_empty() => write!(formatted, ".[{}]", index),
+                Segment::Index(index) => write!(formatted, "[{}]", index),
+                Segment::Name(name) if is_identifier(name) => write!(formatted, ".{}", name),
+                Segment::Name(name) => write!(formatted, ".{:?}", name),
+                Segment::Unknown => write!(formatted, ".?"),
+            };
+        }
+        match formatted.is_empty() {
+            true => ".".to_string(),
+            false => formatted,
+        }
+    }
+}
+
+/// Whether `name` can be written unquoted in a path.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+struct GuardedDeserializer<'a, D> {
+    inner: D,
+    state: &'a State,
+    /// Set when this deserializes a map key or variant, which is captured for the path, with the
+    /// field or variant names the key is borrowed from.
+    capture: Option<Names>,
+}
+
+impl<'a, D> GuardedDeserializer<'a, D> {
+    fn new(inner: D, state: &'a State, capture: Option<Names>) -> Self {
+        Self {
+            inner,
+            state,
+            capture,
+        }
+    }
+
+    fn visitor<V>(&self, visitor: V, names: Option<Names>) -> GuardedVisitor<'a, V> {
+        GuardedVisitor {
+            inner: visitor,
+            state: self.state,
+            names,
+            capture: self.capture,
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> std::result::Result<V::Value, D::Error> {
+                let visitor = self.visitor(visitor, None);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a, D: Deserializer<'de>> Deserializer<'de> for GuardedDeserializer<'a, D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error> {
+        let visitor = self.visitor(visitor, Some(fields));
+        self.inner.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error> {
+        let visitor = self.visitor(visitor, Some(variants));
+        self.inner.deserialize_enum(name, variants, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+struct GuardedVisitor<'a, V> {
+    inner: V,
+    state: &'a State,
+    /// Names of the struct fields or enum variants, used to name the items of structs encoded as
+    /// sequences, and variants or fields identified by their index.
+    names: Option<Names>,
+    capture: Option<Names>,
+}
+
+impl<'a, V> GuardedVisitor<'a, V> {
+    fn access<A>(&self, inner: A) -> GuardedAccess<'a, A> {
+        GuardedAccess {
+            inner,
+            state: self.state,
+            names: self.names,
+            count: 0,
+            key: None,
+        }
+    }
+
+    fn capture(&self, key: impl FnOnce(Names) -> Segment) {
+        if let Some(names) = self.capture {
+            self.state.capture(key(names));
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> std::result::Result<V::Value, E> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_visit_integer {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> std::result::Result<V::Value, E> {
+                self.capture(|_| match usize::try_from(v) {
+                    Ok(index) => Segment::Index(index),
+                    Err(_) => Segment::Name(Cow::Owned(v.to_string())),
+                });
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_visit_string {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> std::result::Result<V::Value, E> {
+                self.state.string(v.len())?;
+                self.capture(|names| Segment::name(&v, names));
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a, V: Visitor<'de>> Visitor<'de> for GuardedVisitor<'a, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+    }
+
+    forward_visit_integer! {
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+    }
+
+    forward_visit_string! {
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    }
+
+    fn visit_none<E: de::Error>(self) -> std::result::Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> std::result::Result<V::Value, D::Error> {
+        self.inner
+            .visit_some(GuardedDeserializer::new(d, self.state, self.capture))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        d: D,
+    ) -> std::result::Result<V::Value, D::Error> {
+        self.inner
+            .visit_newtype_struct(GuardedDeserializer::new(d, self.state, self.capture))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> std::result::Result<V::Value, A::Error> {
+        self.state.enter(seq.size_hint())?;
+        let access = self.access(seq);
+        let value = self.inner.visit_seq(access);
+        self.state.leave();
+        value
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> std::result::Result<V::Value, A::Error> {
+        self.state.enter(map.size_hint())?;
+        let access = self.access(map);
+        let value = self.inner.visit_map(access);
+        self.state.leave();
+        value
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> std::result::Result<V::Value, A::Error> {
+        self.state.enter(None)?;
+        let access = self.access(data);
+        let value = self.inner.visit_enum(access);
+        self.state.leave();
+        value
+    }
+}
+
+/// Wraps the `SeqAccess`, `MapAccess`, `EnumAccess` and `VariantAccess` of the backend, counting
+/// the items of collections and adding them to the path.
+struct GuardedAccess<'a, A> {
+    inner: A,
+    state: &'a State,
+    names: Option<Names>,
+    count: usize,
+    /// The last map key or the variant, the path segment of the value that follows.
+    key: Option<Segment>,
+}
+
+impl<'a, A> GuardedAccess<'a, A> {
+    fn seed<S>(&self, seed: S, capture: Option<Names>) -> GuardedSeed<'a, S> {
+        GuardedSeed {
+            inner: seed,
+            state: self.state,
+            capture,
+        }
+    }
+
+    fn visitor<V>(&self, visitor: V, names: Option<Names>) -> GuardedVisitor<'a, V> {
+        GuardedVisitor {
+            inner: visitor,
+            state: self.state,
+            names,
+            capture: None,
+        }
+    }
+
+    fn count<T, E: de::Error>(&mut self, item: &Option<T>) -> std::result::Result<(), E> {
+        if item.is_some() {
+            self.count += 1;
+            self.state.item(self.count)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de, 'a, A: SeqAccess<'de>> SeqAccess<'de> for GuardedAccess<'a, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> std::result::Result<Option<S::Value>, A::Error> {
+        let segment = Segment::item(self.count, self.names);
+        let seed = self.seed(seed, None);
+        let element = self
+            .state
+            .scoped(segment, || self.inner.next_element_seed(seed))?;
+        self.count(&element)?;
+        Ok(element)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'a, A: MapAccess<'de>> MapAccess<'de> for GuardedAccess<'a, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> std::result::Result<Option<S::Value>, A::Error> {
+        self.state.key.borrow_mut().take();
+        let key = self
+            .inner
+            .next_key_seed(self.seed(seed, Some(self.names.unwrap_or_default())))?;
+        self.key = Some(self.state.captured(self.names));
+        self.count(&key)?;
+        Ok(key)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> std::result::Result<S::Value, A::Error> {
+        let segment = self.key.take().unwrap_or(Segment::Unknown);
+        let seed = self.seed(seed, None);
+        self.state
+            .scoped(segment, || self.inner.next_value_seed(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'a, A: EnumAccess<'de>> EnumAccess<'de> for GuardedAccess<'a, A> {
+    type Error = A::Error;
+    type Variant = GuardedAccess<'a, A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> std::result::Result<(S::Value, Self::Variant), A::Error> {
+        let (state, names) = (self.state, self.names);
+        state.key.borrow_mut().take();
+        let seed = self.seed(seed, Some(names.unwrap_or_default()));
+        let (value, variant) = self.inner.variant_seed(seed)?;
+        Ok((
+            value,
+            GuardedAccess {
+                inner: variant,
+                state,
+                names: None,
+                count: 0,
+                key: Some(state.captured(names)),
+            },
+        ))
+    }
+}
+
+impl<'de, 'a, A: VariantAccess<'de>> VariantAccess<'de> for GuardedAccess<'a, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> std::result::Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> std::result::Result<S::Value, A::Error> {
+        let segment = self.key.clone().unwrap_or(Segment::Unknown);
+        let seed = self.seed(seed, None);
+        self.state
+            .scoped(segment, || self.inner.newtype_variant_seed(seed))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> std::result::Result<V::Value, A::Error> {
+        let segment = self.key.clone().unwrap_or(Segment::Unknown);
+        let visitor = self.visitor(visitor, None);
+        self.state
+            .scoped(segment, || self.inner.tuple_variant(len, visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, A::Error> {
+        let segment = self.key.clone().unwrap_or(Segment::Unknown);
+        let visitor = self.visitor(visitor, Some(fields));
+        self.state
+            .scoped(segment, || self.inner.struct_variant(fields, visitor))
+    }
+}
+
+struct GuardedSeed<'a, S> {
+    inner: S,
+    state: &'a State,
+    capture: Option<Names>,
+}
+
+impl<'de, 'a, S: DeserializeSeed<'de>> DeserializeSeed<'de> for GuardedSeed<'a, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> std::result::Result<S::Value, D::Error> {
+        self.inner
+            .deserialize(GuardedDeserializer::new(d, self.state, self.capture))
+    }
+}
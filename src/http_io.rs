@@ -0,0 +1,122 @@
+//! # Http
+//! Conversions between `ContentType` and `http::HeaderValue`, and helpers for encoding responses
+//! and decoding requests of the `http` crate, enabled with the `http` feature.
+//!
+//! A header is read as a `MediaType`, converting it into a `ContentType` ignores its parameters,
+//! `decode_request` hands them to the decoder. The header written for a `ContentType` is its
+//! `ContentType::mime`.
+//!
+//! ```rust
+//! use http::header::CONTENT_TYPE;
+//! use http::Request;
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::http_io::{decode_request, encode_response};
+//! use simple_serde::Decoded;
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let request = Request::post("/foo")
+//!     .header(CONTENT_TYPE, "application/json; charset=utf-8")
+//!     .body(r#"{"bar": "foobar"}"#)
+//!     .unwrap();
+//! let decoded: Decoded<Foo> = decode_request(&request).unwrap();
+//!
+//! let response = encode_response(&decoded.into(), "yaml").unwrap();
+//! assert_eq!("application/yaml", response.headers()[CONTENT_TYPE]);
+//! assert_eq!(b"bar: foobar\n".to_vec(), *response.body());
+//! ```
+
+use crate::{
+    ContentType, DecodeOptions, Decoded, Encoded, Error, MediaType, Result, SimpleDecoder,
+    SimpleEncoder,
+};
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue, Request, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+impl TryFrom<HeaderValue> for ContentType {
+    type Error = Error;
+
+    fn try_from(h: HeaderValue) -> Result<ContentType> {
+        ContentType::try_from(&h)
+    }
+}
+
+impl TryFrom<&HeaderValue> for ContentType {
+    type Error = Error;
+
+    fn try_from(h: &HeaderValue) -> Result<ContentType> {
+        h.to_str()
+            .map_err(Error::from)
+            .and_then(ContentType::try_from)
+    }
+}
+
+impl TryFrom<&HeaderValue> for MediaType {
+    type Error = Error;
+
+    fn try_from(h: &HeaderValue) -> Result<MediaType> {
+        h.to_str().map_err(Error::from).and_then(MediaType::parse)
+    }
+}
+
+impl TryFrom<ContentType> for HeaderValue {
+    type Error = Error;
+
+    fn try_from(content_type: ContentType) -> Result<HeaderValue> {
+        HeaderValue::try_from(&content_type)
+    }
+}
+
+impl TryFrom<&ContentType> for HeaderValue {
+    type Error = Error;
+
+    fn try_from(content_type: &ContentType) -> Result<HeaderValue> {
+        HeaderValue::from_str(content_type.mime()).map_err(Error::from)
+    }
+}
+
+impl Encoded {
+    /// The headers to send the data with, holding its `Content-Type`, and the body. Fails with
+    /// `Error::ContentTypeNotDetected` when the format is not known.
+    pub fn into_http_parts(self) -> Result<(HeaderMap, Vec<u8>)> {
+        let content_type = self.content_type().ok_or(Error::ContentTypeNotDetected)?;
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::try_from(content_type)?);
+        Ok((headers, self.into_inner()))
+    }
+}
+
+/// Encode `value` into the body of a response, with the `Content-Type` header of the format.
+pub fn encode_response<T, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    value: &T,
+    content_type: F,
+) -> Result<Response<Vec<u8>>>
+where
+    T: Serialize,
+{
+    let (headers, body) = value.encode(content_type)?.into_http_parts()?;
+    let mut response = Response::new(body);
+    *response.headers_mut() = headers;
+    Ok(response)
+}
+
+/// Decode the body of `request` with the format and parameters of its `Content-Type` header.
+pub fn decode_request<T, B>(request: &Request<B>) -> Result<Decoded<T>>
+where
+    T: DeserializeOwned,
+    B: AsRef<[u8]>,
+{
+    let media_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .ok_or(Error::ContentTypeNotDetected)
+        .and_then(MediaType::try_from)?;
+    let content_type = media_type.content_type()?;
+    let options = DecodeOptions::new().media_type(media_type);
+    request.body().as_ref().decode_with(content_type, &options)
+}
@@ -0,0 +1,453 @@
+//! # Kind
+//! Classifying errors independent of the format, e.g. for an HTTP layer mapping errors to status
+//! codes without matching the error of every backend.
+//!
+//! | Kind                     | Cause                                                       |
+//! |--------------------------|-------------------------------------------------------------|
+//! | `Syntax`                 | The input is not valid for the format                       |
+//! | `Data`                   | Valid input not matching the type, e.g. a missing field     |
+//! | `Eof`                    | The input ended early                                       |
+//! | `UnsupportedType`        | The value can not be represented by the format              |
+//! | `UnsupportedContentType` | The content type or charset is unsupported or undetected    |
+//! | `Unsupported`            | The format does not support the operation or option         |
+//! | `LimitExceeded`          | A `Limits` or the maximum frame size was exceeded           |
+//! | `Io`                     | Reading or writing failed                                   |
+//! | `Other`                  | Anything else                                               |
+//!
+//! Errors of the backends while encoding are returned as `Error::EncodeFailure`, like decoding
+//! errors are returned as `Error::DecodeFailure`, so the direction of an error from `encode`,
+//! `encode_with` or `encode_to_writer` is always known. Encoding has no input to be malformed, so
+//! backend errors classified as `Syntax` or `Eof`, like a JSON map key that is not a string, are
+//! reported as `UnsupportedType` when encoding.
+//!
+//! Some backends, like TOML and YAML, only expose a message, in which case the kind is based on
+//! the messages serde uses for type mismatches. Json5 and Ron parse the input as the type asks for
+//! it, so a value of the wrong type is reported as `Syntax`.
+//!
+//! ```rust
+//! use simple_serde::{ContentType, Decoded, Direction, ErrorKind, SimpleDecoder};
+//!
+//! let decoded: simple_serde::Result<Decoded<u32>> = r#""foobar""#.decode("json");
+//! let e = decoded.map(|_| ()).unwrap_err();
+//! assert_eq!(ErrorKind::Data, e.kind());
+//! assert_eq!(Some(ContentType::Json), e.content_type());
+//! assert_eq!(Some(Direction::Decode), e.direction());
+//! ```
+
+use crate::{ContentType, Error, LimitKind};
+use derive_more::Display;
+use std::any::type_name;
+use std::fmt;
+
+/// The cause of an `Error`, see the module documentation.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    #[display(fmt = "syntax error")]
+    Syntax,
+    #[display(fmt = "invalid data")]
+    Data,
+    #[display(fmt = "unexpected end of input")]
+    Eof,
+    #[display(fmt = "unsupported type")]
+    UnsupportedType,
+    #[display(fmt = "unsupported content type")]
+    UnsupportedContentType,
+    #[display(fmt = "unsupported")]
+    Unsupported,
+    #[display(fmt = "limit exceeded")]
+    LimitExceeded,
+    #[display(fmt = "io error")]
+    Io,
+    #[display(fmt = "other")]
+    Other,
+}
+
+/// Whether an `Error` happened while encoding or decoding.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Encode,
+    Decode,
+}
+
+/// A backend error while encoding, see the module documentation.
+#[derive(Clone, Debug)]
+pub struct EncodeError {
+    content_type: ContentType,
+    type_name: &'static str,
+    error: Error,
+}
+
+impl EncodeError {
+    pub fn content_type(&self) -> &ContentType {
+        &self.content_type
+    }
+
+    /// Name of the type being encoded.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The error of the backend.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed encoding {}: {}", self.type_name, self.error)
+    }
+}
+
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Turn an error encoding `T` as `content_type` into `Error::EncodeFailure`, errors that are not
+/// about the value are returned as they are.
+pub(crate) fn encode_failure<T: ?Sized>(content_type: &ContentType, error: Error) -> Error {
+    match error {
+        // a nested encode, e.g. in a custom codec, already carries its type
+        Error::EncodeFailure(_) => error,
+        _ if matches!(
+            error.kind(),
+            ErrorKind::Unsupported | ErrorKind::UnsupportedContentType
+        ) =>
+        {
+            error
+        }
+        _ => Error::EncodeFailure(Box::new(EncodeError {
+            content_type: content_type.clone(),
+            type_name: type_name::<T>(),
+            error,
+        })),
+    }
+}
+
+impl Error {
+    /// The cause of the error, independent of the format.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Infallible => ErrorKind::Other,
+            Error::ByteToUTF8ConversionFailure(_) => ErrorKind::Syntax,
+            Error::UnknownContentTypeMatchFromStr(_) => ErrorKind::UnsupportedContentType,
+            Error::IoError(e) => io(e),
+            Error::AvroError(e) => avro(e),
+            Error::AvroSchemaNotRegistered(_) => ErrorKind::UnsupportedType,
+            Error::AvroUnexpectedRecordCount(_) => ErrorKind::Data,
+            Error::BsonSerializationFailure(e) => match e {
+                bson::ser::Error::Io(e) => io(e),
+                bson::ser::Error::SerializationError { message, .. } => {
+                    message_kind(message, ErrorKind::Data)
+                }
+                _ => ErrorKind::UnsupportedType,
+            },
+            Error::BsonDeserializationFailure(e) => match &**e {
+                bson::de::Error::Io(e) => io(e),
+                bson::de::Error::EndOfStream => ErrorKind::Eof,
+                bson::de::Error::DeserializationError { message, .. } => {
+                    message_kind(message, ErrorKind::Data)
+                }
+                _ => ErrorKind::Syntax,
+            },
+            Error::CborFailure(e) => match e.classify() {
+                serde_cbor::error::Category::Io => ErrorKind::Io,
+                serde_cbor::error::Category::Syntax => ErrorKind::Syntax,
+                serde_cbor::error::Category::Data => ErrorKind::Data,
+                serde_cbor::error::Category::Eof => ErrorKind::Eof,
+            },
+            Error::FlexBuffersSerializationFailure(e) => match &**e {
+                flexbuffers::SerializationError::Serde(message) => {
+                    message_kind(message, ErrorKind::Data)
+                }
+                _ => ErrorKind::UnsupportedType,
+            },
+            Error::FlexBuffersDeserializationFailure(e) => match e {
+                flexbuffers::DeserializationError::Serde(message) => {
+                    message_kind(message, ErrorKind::Data)
+                }
+                flexbuffers::DeserializationError::Reader(
+                    flexbuffers::ReaderError::UnexpectedFlexbufferType { .. },
+                ) => ErrorKind::Data,
+                flexbuffers::DeserializationError::Reader(_) => ErrorKind::Syntax,
+            },
+            Error::JsonError(e) => match e.classify() {
+                serde_json::error::Category::Io => ErrorKind::Io,
+                serde_json::error::Category::Syntax => ErrorKind::Syntax,
+                serde_json::error::Category::Data => ErrorKind::Data,
+                serde_json::error::Category::Eof => ErrorKind::Eof,
+            },
+            Error::Json5Error(json5::Error::Message { msg, location }) => match location {
+                Some(_) => message_kind(msg, ErrorKind::Syntax),
+                None => ErrorKind::Data,
+            },
+            Error::LexprError(e) => match e.classify() {
+                serde_lexpr::error::Category::Io => ErrorKind::Io,
+                serde_lexpr::error::Category::Syntax => ErrorKind::Syntax,
+                serde_lexpr::error::Category::Data => ErrorKind::Data,
+                serde_lexpr::error::Category::Eof => ErrorKind::Eof,
+            },
+            Error::MessagePackEncodeError(e) => match &**e {
+                rmp_serde::encode::Error::InvalidValueWrite(_) => ErrorKind::Io,
+                rmp_serde::encode::Error::DepthLimitExceeded => ErrorKind::LimitExceeded,
+                rmp_serde::encode::Error::Syntax(message) => message_kind(message, ErrorKind::Data),
+                _ => ErrorKind::UnsupportedType,
+            },
+            Error::MessagePackDecodeError(e) => match &**e {
+                rmp_serde::decode::Error::InvalidMarkerRead(e)
+                | rmp_serde::decode::Error::InvalidDataRead(e) => io(e),
+                rmp_serde::decode::Error::Utf8Error(_) => ErrorKind::Syntax,
+                rmp_serde::decode::Error::DepthLimitExceeded => ErrorKind::LimitExceeded,
+                _ => ErrorKind::Data,
+            },
+            Error::PickleError(e) => match &**e {
+                serde_pickle::Error::Io(e) => io(e),
+                serde_pickle::Error::Eval(serde_pickle::ErrorCode::EOFWhileParsing, _) => {
+                    ErrorKind::Eof
+                }
+                serde_pickle::Error::Eval(_, _) => ErrorKind::Syntax,
+                // conversion between pickle and Rust values, in both directions
+                serde_pickle::Error::Syntax(serde_pickle::ErrorCode::Unsupported(_)) => {
+                    ErrorKind::UnsupportedType
+                }
+                serde_pickle::Error::Syntax(_) => ErrorKind::Data,
+            },
+            Error::PostcardError(e) => match e {
+                postcard::Error::WontImplement
+                | postcard::Error::NotYetImplemented
+                | postcard::Error::SerializeSeqLengthUnknown => ErrorKind::UnsupportedType,
+                postcard::Error::DeserializeUnexpectedEnd => ErrorKind::Eof,
+                postcard::Error::SerdeSerCustom | postcard::Error::SerdeDeCustom => ErrorKind::Data,
+                postcard::Error::SerializeBufferFull => ErrorKind::Other,
+                _ => ErrorKind::Syntax,
+            },
+            Error::RonError(e) => ron(e),
+            Error::RonDecodeError(e) => ron(&e.code),
+            Error::TomlSerializationFailure(e) => match e {
+                toml::ser::Error::Custom(message) => message_kind(message, ErrorKind::Data),
+                toml::ser::Error::DateInvalid | toml::ser::Error::NumberInvalid => ErrorKind::Data,
+                _ => ErrorKind::UnsupportedType,
+            },
+            Error::TomlDeserializationFailure(e) => message_kind(&e.to_string(), ErrorKind::Syntax),
+            Error::UrlEncodingFailure(e) => match &**e {
+                serde_qs::Error::Custom(message) => message_kind(message, ErrorKind::Data),
+                serde_qs::Error::Unsupported => ErrorKind::UnsupportedType,
+                serde_qs::Error::Io(e) => io(e),
+                _ => ErrorKind::Syntax,
+            },
+            Error::YamlError(e) => message_kind(&e.to_string(), ErrorKind::Syntax),
+            #[cfg(feature = "accept-limited-xml-serialize")]
+            Error::XmlError(e) => match &**e {
+                serde_xml_rs::Error::Custom { field } => message_kind(field, ErrorKind::Data),
+                serde_xml_rs::Error::UnsupportedOperation { .. } => ErrorKind::UnsupportedType,
+                serde_xml_rs::Error::Io { .. } | serde_xml_rs::Error::Writer { .. } => {
+                    ErrorKind::Io
+                }
+                _ => ErrorKind::Syntax,
+            },
+            Error::CodecFailure(e) => message_kind(&e.to_string(), ErrorKind::Other),
+            Error::EncodeFailure(e) => match e.error().kind() {
+                ErrorKind::Syntax | ErrorKind::Eof => ErrorKind::UnsupportedType,
+                kind => kind,
+            },
+            Error::DecodeFailure(e) => e.error().kind(),
+            Error::RecordFailure(e) => e.error().kind(),
+            Error::UnsupportedOption(_, _) => ErrorKind::Unsupported,
+            Error::InvalidMediaType(_) => ErrorKind::UnsupportedContentType,
+            Error::UnsupportedCharset(_, _) => ErrorKind::UnsupportedContentType,
+            Error::LimitExceeded { .. } => ErrorKind::LimitExceeded,
+            Error::ContentTypeNotDetected => ErrorKind::UnsupportedContentType,
+            Error::UnknownExtension(_) => ErrorKind::UnsupportedContentType,
+            Error::NotAcceptable(_) => ErrorKind::UnsupportedContentType,
+            Error::TypeDoesNotSupportSerialization(_) => ErrorKind::UnsupportedContentType,
+            Error::BorrowedDecodingNotSupported(_) => ErrorKind::Unsupported,
+            Error::TranscodingNotSupported(_) => ErrorKind::Unsupported,
+            Error::StreamingNotSupported(_) => ErrorKind::Unsupported,
+            Error::InvalidFrame(_, _) => ErrorKind::Syntax,
+            #[cfg(feature = "http")]
+            Error::FailedConvertingHeaderValueToContentType(_) => ErrorKind::UnsupportedContentType,
+            #[cfg(feature = "http")]
+            Error::InvalidHeaderValue(_) => ErrorKind::Other,
+        }
+    }
+
+    /// The format the error belongs to, `None` for errors that are not specific to a format,
+    /// including errors from a custom codec, unless wrapped in `Error::EncodeFailure` or
+    /// `Error::DecodeFailure`.
+    pub fn content_type(&self) -> Option<ContentType> {
+        match self {
+            Error::AvroError(_)
+            | Error::AvroSchemaNotRegistered(_)
+            | Error::AvroUnexpectedRecordCount(_) => Some(ContentType::Avro),
+            Error::BsonSerializationFailure(_) | Error::BsonDeserializationFailure(_) => {
+                Some(ContentType::Bson)
+            }
+            Error::CborFailure(_) => Some(ContentType::Cbor),
+            Error::FlexBuffersSerializationFailure(_)
+            | Error::FlexBuffersDeserializationFailure(_) => Some(ContentType::FlexBuffers),
+            Error::JsonError(_) => Some(ContentType::Json),
+            Error::Json5Error(_) => Some(ContentType::Json5),
+            Error::LexprError(_) => Some(ContentType::Lexpr),
+            Error::MessagePackEncodeError(_) | Error::MessagePackDecodeError(_) => {
+                Some(ContentType::MessagePack)
+            }
+            Error::PickleError(_) => Some(ContentType::Pickle),
+            Error::PostcardError(_) => Some(ContentType::Postcard),
+            Error::RonError(_) | Error::RonDecodeError(_) => Some(ContentType::Ron),
+            Error::TomlSerializationFailure(_) | Error::TomlDeserializationFailure(_) => {
+                Some(ContentType::Toml)
+            }
+            Error::UrlEncodingFailure(_) => Some(ContentType::Url),
+            Error::YamlError(_) => Some(ContentType::Yaml),
+            #[cfg(feature = "accept-limited-xml-serialize")]
+            Error::XmlError(_) => Some(ContentType::Xml),
+            Error::EncodeFailure(e) => Some(e.content_type().clone()),
+            Error::DecodeFailure(e) => Some(e.content_type().clone()),
+            Error::RecordFailure(e) => Some(e.content_type().clone()),
+            Error::UnsupportedOption(content_type, _)
+            | Error::UnsupportedCharset(content_type, _)
+            | Error::TypeDoesNotSupportSerialization(content_type)
+            | Error::BorrowedDecodingNotSupported(content_type)
+            | Error::TranscodingNotSupported(content_type)
+            | Error::StreamingNotSupported(content_type)
+            | Error::InvalidFrame(content_type, _) => Some(content_type.clone()),
+            _ => None,
+        }
+    }
+
+    /// Whether the error happened while encoding or decoding. Backends using the same error for
+    /// both, like `Error::JsonError`, give `None`, unless wrapped in `Error::EncodeFailure` or
+    /// `Error::DecodeFailure` which is how the encoders and decoders of this crate return them.
+    pub fn direction(&self) -> Option<Direction> {
+        match self {
+            // frames are limited both ways
+            Error::LimitExceeded {
+                kind: LimitKind::FrameBytes,
+                ..
+            } => None,
+            Error::BsonSerializationFailure(_)
+            | Error::FlexBuffersSerializationFailure(_)
+            | Error::MessagePackEncodeError(_)
+            | Error::TomlSerializationFailure(_)
+            | Error::AvroSchemaNotRegistered(_)
+            | Error::NotAcceptable(_)
+            | Error::EncodeFailure(_) => Some(Direction::Encode),
+            Error::BsonDeserializationFailure(_)
+            | Error::FlexBuffersDeserializationFailure(_)
+            | Error::MessagePackDecodeError(_)
+            | Error::RonDecodeError(_)
+            | Error::TomlDeserializationFailure(_)
+            | Error::AvroUnexpectedRecordCount(_)
+            | Error::DecodeFailure(_)
+            | Error::RecordFailure(_)
+            | Error::InvalidFrame(_, _)
+            | Error::LimitExceeded { .. }
+            | Error::ContentTypeNotDetected
+            | Error::UnsupportedCharset(_, _)
+            | Error::BorrowedDecodingNotSupported(_) => Some(Direction::Decode),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// The HTTP status code of a request failing with the error.
+    #[cfg(any(feature = "actix-web", feature = "axum"))]
+    pub(crate) fn status(&self) -> u16 {
+        match (self, self.kind(), self.direction()) {
+            (Error::NotAcceptable(_), _, _) => 406,
+            (_, ErrorKind::UnsupportedContentType | ErrorKind::Unsupported, _) => 415,
+            (_, _, Some(Direction::Decode)) => 400,
+            _ => 500,
+        }
+    }
+}
+
+fn io(e: &std::io::Error) -> ErrorKind {
+    match e.kind() {
+        std::io::ErrorKind::UnexpectedEof => ErrorKind::Eof,
+        _ => ErrorKind::Io,
+    }
+}
+
+fn avro(e: &avro_rs::Error) -> ErrorKind {
+    use avro_rs::Error::*;
+    match e {
+        ReadBoolean(e)
+        | ReadBytes(e)
+        | ReadString(e)
+        | ReadDouble(e)
+        | ReadFloat(e)
+        | ReadDuration(e)
+        | ReadFixed(e, _)
+        | ReadHeader(e)
+        | ReadMarker(e)
+        | ReadBlockMarker(e)
+        | ReadIntoBuf(e)
+        | ReadVariableIntegerBytes(e) => io(e),
+        WriteBytes(_) | WriteMarker(_) => ErrorKind::Io,
+        HeaderMagic
+        | GetHeaderMetadata
+        | GetAvroSchemaFromMap
+        | GetBlockMarker
+        | ReadBlock
+        | SnappyCrc32 { .. }
+        | DeflateDecompress(_)
+        | SnappyDecompress(_)
+        | GetSnappyDecompressLen(_)
+        | IntegerOverflow
+        | ConvertToUtf8(_)
+        | BoolValue(_) => ErrorKind::Syntax,
+        MemoryAllocation { .. } => ErrorKind::LimitExceeded,
+        SerializeValue(message) | DeserializeValue(message) => {
+            message_kind(message, ErrorKind::Data)
+        }
+        _ => ErrorKind::Data,
+    }
+}
+
+fn ron(e: &ron::Error) -> ErrorKind {
+    match e {
+        ron::Error::Io(_) => ErrorKind::Io,
+        ron::Error::Eof => ErrorKind::Eof,
+        ron::Error::Message(message) => message_kind(message, ErrorKind::Data),
+        ron::Error::InvalidValueForType { .. }
+        | ron::Error::ExpectedDifferentLength { .. }
+        | ron::Error::ExpectedDifferentStructName { .. }
+        | ron::Error::NoSuchEnumVariant { .. }
+        | ron::Error::NoSuchStructField { .. }
+        | ron::Error::MissingStructField { .. }
+        | ron::Error::DuplicateStructField { .. }
+        | ron::Error::IntegerOutOfBounds => ErrorKind::Data,
+        _ => ErrorKind::Syntax,
+    }
+}
+
+/// Classify an error by its message, using the messages of `serde::de::Error`, `otherwise` when
+/// the message is not recognized.
+fn message_kind(message: &str, otherwise: ErrorKind) -> ErrorKind {
+    const DATA: [&str; 7] = [
+        "invalid type: ",
+        "invalid value: ",
+        "invalid length ",
+        "unknown variant `",
+        "unknown field `",
+        "missing field `",
+        "duplicate field `",
+    ];
+    let lowercase = message.to_lowercase();
+    // some backends prefix the message with the path
+    if DATA.iter().any(|data| lowercase.contains(data)) {
+        ErrorKind::Data
+    } else if lowercase.contains("eof") || lowercase.contains("end of input") {
+        ErrorKind::Eof
+    } else {
+        otherwise
+    }
+}
@@ -16,6 +16,9 @@
 //! from/to.
 //! an example would be `[some Vec<u8>].decode("bson")` or `my_struct.encode("bson")`.
 //! This is possible as `ContentType` implements the `TryFrom` trait for `&str`, `String`.
+//! Media types with parameters or a structured syntax suffix, like `application/json;
+//! charset=utf-8` or `application/problem+cbor`, are accepted as well, see `media_type` module
+//! documentation.
 //! In case the implementation is unable to decode what type you are trying to encode/decode from/to
 //! an `Err` result with `Error::UnknownContentTypeMatchFromStr` will be returned from the
 //! encoder/decoder
@@ -26,12 +29,115 @@
 //! `Encoded` had implemented the `Deref` and `DerefMut` traits to make it easier to gain access to
 //! encapsulated data.
 //!
+//! `Encoded` remembers the `ContentType` and `EncodeOptions` it was encoded with, so
+//! `encoded.decode()` decodes it back without naming the format again, and with the `http` feature
+//! `encoded.into_http_parts()` gives the headers and body to send it with.
+//!
+//! ## Borrowed decoding
+//! `.decode` requires the decoded type to own all of its data. `.decode_borrowed` allows decoding
+//! into types borrowing from the input, like `&'de str` or `&'de [u8]` fields, the decoded value
+//! is then tied to the lifetime of the input.
+//!
+//! Only formats where the backend is able to hand out borrowed data are supported: Bson, Cbor,
+//! FlexBuffers, Json, JsonLines, MessagePack, Postcard, Ron, Toml, Url and Yaml. Any other format
+//! returns `Error::BorrowedDecodingNotSupported`. Text formats can only borrow strings without
+//! escape sequences, use `Cow<'de, str>` with `#[serde(borrow)]` if the input might contain
+//! escapes.
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use simple_serde::{Decoded, SimpleBorrowedDecoder};
+//!
+//! #[derive(Deserialize, Debug, PartialEq)]
+//! struct Foo<'a> {
+//!     bar: &'a str,
+//! }
+//!
+//! let data = r#"{"bar": "foobar"}"#.to_string();
+//! let decoded: Decoded<Foo> = data.decode_borrowed("json").unwrap();
+//! assert_eq!(Foo { bar: "foobar" }, decoded.into());
+//! ```
+//!
+//! ## Encoder/Decoder options
+//! `.encode_with` takes `EncodeOptions` configuring the backends, like pretty printing, and
+//! `.decode_with` takes `DecodeOptions` with limits and format specific settings, see `options`
+//! module documentation for details.
+//!
+//! ## Limits
+//! Untrusted input can be decoded with `Limits` on the input size, nesting depth, collection and
+//! string lengths, set through `DecodeOptions::limits`. The limits apply to every format, and
+//! exceeding one fails with `Error::LimitExceeded`, see `limits` module documentation.
+//!
+//! ## Decode errors
+//! Decoding failures are returned as `Error::DecodeFailure`, with the path of the field that
+//! failed, the position in the input when the backend reports it and the backend error, see `path`
+//! module documentation.
+//!
+//! Encoding failures are returned as `Error::EncodeFailure` in the same way, holding the backend
+//! error and the name of the type being encoded.
+//!
+//! `Error::kind`, `Error::content_type` and `Error::direction` classify any error independent of
+//! the format, e.g. for mapping errors to HTTP status codes, see `kind` module documentation.
+//!
+//! ## Custom formats
+//! Formats that are not built in can be added by implementing `codec::Codec` and registering it,
+//! after which it can be used by its MIME type or aliases like any built-in format. See `codec`
+//! module documentation for details.
+//!
+//! ## Content sniffing
+//! `ContentType::detect` guesses the content type of a payload from its magic bytes and structure,
+//! and `.decode_auto` decodes using the most likely content type that decodes successfully. See
+//! `detect` module documentation for details.
+//!
+//! ## Transcoding
+//! `transcode` converts data between two formats without decoding it into a Rust type first, see
+//! `transcode` module documentation for details.
+//!
+//! ## Streaming
+//! `.encode_to_writer` and `decode_from_reader` works on any `std::io::Write`/`std::io::Read`
+//! instead of a `Vec<u8>`. Most formats are streamed directly to/from the underlying backend,
+//! however some backends only work on complete slices, these formats are buffered internally:
+//! - Encoding: Bson, FlexBuffers, Json5, Postcard, Toml
+//! - Decoding: FlexBuffers, Json5, Postcard, Ron, Toml, Url, Yaml
+//!
+//! With the `tokio` feature enabled, `async_io` provides the same over tokio's
+//! `AsyncWrite`/`AsyncRead`.
+//!
+//! ## Record streams
+//! `encode_stream` and `decode_stream` write and lazily read many values as records of one stream,
+//! like JSON Lines, multi-document YAML, CBOR sequences or COBS framed Postcard, with the index
+//! and line of a record in its errors.
+//! `encode_all` and `decode_all` do the same in memory, see `stream` module documentation.
+//!
+//! With the `tokio-util` feature enabled, `framed::SimpleCodec` is a tokio-util codec sending
+//! values as length prefixed, newline or COBS delimited frames, see `framed` module documentation.
+//!
+//! ## Files
+//! `load` and `save` read and write files in the format of their extension, `save` replacing the
+//! file atomically, see `file` module documentation. `ContentType::from_extension` and
+//! `ContentType::from_path` give the format of an extension.
+//!
+//! ## Web frameworks
+//! `ContentType::negotiate` picks the format of a response from an `Accept` header with q-values
+//! and wildcards, see `negotiate` module documentation.
+//!
+//! With the `actix-web` feature enabled, `actix` provides an extractor and a responder using the
+//! `Content-Type` and `Accept` headers to pick the format, see `actix` module documentation. The
+//! `axum` feature provides the same for axum, see `axum` module documentation.
+//!
+//! With the `http` feature enabled, `ContentType` converts to and from `http::HeaderValue`, and
+//! `http_io` encodes responses and decodes requests of the `http` crate. The `mime` feature adds
+//! conversions to and from `mime::Mime`, and the `actix-http` feature implements actix's
+//! `TryIntoHeaderValue`.
+//!
 //! ## Supported formats
+//! - Avro (requires a schema, see `avro`)
 //! - Bson
 //! - Cbor
 //! - FlexBuffers
 //! - Json
 //! - Json5
+//! - JsonLines (a single value per line, see `stream` for many)
 //! - Lexpr
 //! - MessagePack
 //! - Pickle
@@ -46,6 +152,10 @@
 //! - `application/[format]`
 //! - `application/x-[format]`
 //!
+//! as well as the MIME types seen in the wild, like `application/vnd.msgpack` or `text/yaml`.
+//! `ContentType::mime` is the canonical MIME type, IANA registered where there is one, and
+//! `ContentType::aliases` lists the other names.
+//!
 //! ## Serialization/Encode example
 //! ```rust
 //! use std::ops::Deref;
@@ -109,11 +219,16 @@
 //! );
 //! ```
 
+extern crate avro_rs;
 extern crate bson;
+extern crate cobs;
+extern crate erased_serde;
 extern crate flexbuffers;
 #[cfg(feature = "http")]
 extern crate http;
 extern crate json5;
+#[cfg(feature = "mime")]
+extern crate mime;
 extern crate postcard;
 extern crate rmp_serde;
 extern crate ron;
@@ -130,8 +245,34 @@ extern crate serde_yaml;
 
 use core::str::from_utf8;
 
+#[cfg(feature = "actix-web")]
+pub mod actix;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+pub mod avro;
+#[cfg(feature = "axum")]
+pub mod axum;
+pub mod codec;
+pub mod detect;
+pub mod file;
+#[cfg(feature = "tokio-util")]
+pub mod framed;
+mod guard;
+#[cfg(feature = "http")]
+pub mod http_io;
+pub mod kind;
+pub mod limits;
+pub mod media_type;
+pub mod negotiate;
+pub mod options;
+pub mod path;
+pub mod stream;
+pub mod transcode;
+
 pub mod prelude {
+    pub extern crate avro_rs;
     pub extern crate bson;
+    pub extern crate erased_serde;
     pub extern crate flexbuffers;
     pub extern crate json5;
     pub extern crate postcard;
@@ -148,29 +289,43 @@ pub mod prelude {
     pub extern crate serde_yaml as yaml;
 }
 
-#[cfg(feature = "http")]
-use crate::Error::InvalidHeaderValue;
-#[cfg(feature = "http")]
+#[cfg(feature = "actix-http")]
 use actix_http::header::TryIntoHeaderValue;
 use derive_more::Display;
-#[cfg(feature = "http")]
-use http::{header::ToStrError, HeaderValue};
-use ron::de::SpannedError;
+use guard::Guarded;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::convert::{Infallible, Into, TryFrom, TryInto};
+use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
+use std::path::Path;
 use std::str::Utf8Error;
+use std::sync::Arc;
+
+pub use detect::Confidence;
+pub use file::{load, save};
+pub use kind::{Direction, EncodeError, ErrorKind};
+pub use limits::{LimitKind, Limits};
+pub use media_type::MediaType;
+pub use options::{DecodeOptions, EncodeOptions};
+pub use path::{DecodeError, Position};
+pub use stream::{
+    decode_all, decode_all_with, decode_stream, decode_stream_with, encode_all, encode_stream,
+    RecordError,
+};
+pub use transcode::transcode;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub enum ContentType {
+    Avro,
     Bson,
     Cbor,
     FlexBuffers,
     Json,
     Json5,
+    JsonLines,
     Lexpr,
     MessagePack,
     Pickle,
@@ -181,63 +336,238 @@ pub enum ContentType {
     Yaml,
     #[cfg(feature = "accept-limited-xml-serialize")]
     Xml,
+    /// A format registered through `codec`.
+    Custom(Arc<dyn codec::Codec>),
 }
 
 impl TryFrom<&str> for ContentType {
     type Error = crate::Error;
 
     fn try_from(s: &str) -> std::result::Result<ContentType, Self::Error> {
-        match s.to_lowercase().as_str() {
-            "bson" => Ok(ContentType::Bson),
-            "application/bson" => Ok(ContentType::Bson),
-            "application/x-bson" => Ok(ContentType::Bson),
-            "cbor" => Ok(ContentType::Cbor),
-            "application/cbor" => Ok(ContentType::Cbor),
-            "application/x-cbor" => Ok(ContentType::Cbor),
-            "flexbuffers" => Ok(ContentType::FlexBuffers),
-            "application/flexbuffers" => Ok(ContentType::FlexBuffers),
-            "application/x-flexbuffers" => Ok(ContentType::FlexBuffers),
-            "json" => Ok(ContentType::Json),
-            "application/json" => Ok(ContentType::Json),
-            "application/x-json" => Ok(ContentType::Json),
-            "json5" => Ok(ContentType::Json5),
-            "application/json5" => Ok(ContentType::Json5),
-            "application/x-json5" => Ok(ContentType::Json5),
-            "lexpr" => Ok(ContentType::Lexpr),
-            "application/lexpr" => Ok(ContentType::Lexpr),
-            "application/x-lexpr" => Ok(ContentType::Lexpr),
-            "messagepack" => Ok(ContentType::MessagePack),
-            "application/messagepack" => Ok(ContentType::MessagePack),
-            "application/x-messagepack" => Ok(ContentType::MessagePack),
-            "pickle" => Ok(ContentType::Pickle),
-            "application/pickle" => Ok(ContentType::Pickle),
-            "application/x-pickle" => Ok(ContentType::Pickle),
-            "postcard" => Ok(ContentType::Postcard),
-            "application/postcard" => Ok(ContentType::Postcard),
-            "application/x-postcard" => Ok(ContentType::Postcard),
-            "ron" => Ok(ContentType::Ron),
-            "application/ron" => Ok(ContentType::Ron),
-            "application/x-ron" => Ok(ContentType::Ron),
-            "toml" => Ok(ContentType::Toml),
-            "application/toml" => Ok(ContentType::Toml),
-            "application/x-toml" => Ok(ContentType::Toml),
-            "url" => Ok(ContentType::Url),
-            "application/url" => Ok(ContentType::Url),
-            "application/x-url" => Ok(ContentType::Url),
-            "yaml" => Ok(ContentType::Yaml),
-            "application/yaml" => Ok(ContentType::Yaml),
-            "application/x-yaml" => Ok(ContentType::Yaml),
-            #[cfg(feature = "accept-limited-xml-serialize")]
-            "xml" => Ok(ContentType::Xml),
+        ContentType::named(s).or_else(|e| {
+            media_type::MediaType::parse(s)
+                .and_then(|media_type| media_type.content_type())
+                .map_err(|_| e)
+        })
+    }
+}
+
+impl ContentType {
+    /// Resolve `s` to a registered codec or a built-in format, without parsing it as a media type.
+    pub(crate) fn named(s: &str) -> Result<ContentType> {
+        match codec::registered(s) {
+            Some(content_type) => Ok(content_type),
+            None => ContentType::builtin(s),
+        }
+    }
+
+    /// Resolve `s` to a built-in format, ignoring any codec registered under the same name.
+    pub(crate) fn builtin(s: &str) -> Result<ContentType> {
+        BUILTIN
+            .iter()
+            .find(|content_type| {
+                content_type
+                    .names()
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(s))
+            })
+            .cloned()
+            .ok_or_else(|| Error::UnknownContentTypeMatchFromStr(s.to_string()))
+    }
+
+    /// The MIME type used in `Content-Type` headers, the type registered with IANA for the formats
+    /// that have one.
+    pub fn mime(&self) -> &str {
+        match self {
+            ContentType::Custom(codec) => codec.mime(),
+            _ => self.names()[0],
+        }
+    }
+
+    /// The other names the format is known by, MIME types as well as short names like `json`.
+    /// Empty for a custom codec, whose aliases are held by the registry it is registered in.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        self.names().get(1..).unwrap_or_default()
+    }
+
+    /// The format of files with the extension `extension`, with or without the leading dot.
+    /// Only built-in formats have extensions.
+    pub fn from_extension(extension: &str) -> Option<ContentType> {
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        BUILTIN
+            .iter()
+            .find(|content_type| {
+                content_type
+                    .extensions()
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(extension))
+            })
+            .cloned()
+    }
+
+    /// The format of the file at `path`, by its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<ContentType> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        ContentType::from_extension(extension)
+    }
+
+    /// The usual file extension of the format, without the leading dot. `None` for Url and custom
+    /// codecs.
+    pub fn extension(&self) -> Option<&'static str> {
+        self.extensions().first().copied()
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            ContentType::Avro => &["avro"],
+            ContentType::Bson => &["bson"],
+            ContentType::Cbor => &["cbor"],
+            ContentType::FlexBuffers => &["flexbuffers"],
+            ContentType::Json => &["json"],
+            ContentType::Json5 => &["json5"],
+            ContentType::JsonLines => &["jsonl", "ndjson"],
+            ContentType::Lexpr => &["sexp", "lexpr"],
+            ContentType::MessagePack => &["msgpack", "mpk"],
+            ContentType::Pickle => &["pkl", "pickle"],
+            ContentType::Postcard => &["postcard"],
+            ContentType::Ron => &["ron"],
+            ContentType::Toml => &["toml"],
+            ContentType::Url => &[],
+            ContentType::Yaml => &["yaml", "yml"],
             #[cfg(feature = "accept-limited-xml-serialize")]
-            "application/xml" => Ok(ContentType::Xml),
+            ContentType::Xml => &["xml"],
+            ContentType::Custom(_) => &[],
+        }
+    }
+
+    /// The canonical MIME type followed by the aliases.
+    fn names(&self) -> &'static [&'static str] {
+        match self {
+            ContentType::Avro => &[
+                "application/avro",
+                "avro",
+                "application/x-avro",
+                "avro/binary",
+            ],
+            ContentType::Bson => &[
+                "application/x-bson",
+                "bson",
+                "application/bson",
+                "application/vnd.mongodb.bson",
+            ],
+            ContentType::Cbor => &[
+                "application/cbor",
+                "cbor",
+                "application/x-cbor",
+                "application/cbor-seq",
+            ],
+            ContentType::FlexBuffers => &[
+                "application/x-flexbuffers",
+                "flexbuffers",
+                "application/flexbuffers",
+            ],
+            ContentType::Json => &[
+                "application/json",
+                "json",
+                "application/x-json",
+                "text/json",
+                "text/x-json",
+            ],
+            ContentType::Json5 => &[
+                "application/json5",
+                "json5",
+                "application/x-json5",
+                "text/json5",
+            ],
+            ContentType::JsonLines => &[
+                "application/x-ndjson",
+                "jsonl",
+                "jsonlines",
+                "ndjson",
+                "application/jsonl",
+                "application/x-jsonl",
+                "application/jsonlines",
+                "application/x-jsonlines",
+                "application/ndjson",
+            ],
+            ContentType::Lexpr => &[
+                "application/x-lexpr",
+                "lexpr",
+                "sexp",
+                "application/lexpr",
+                "application/x-sexp",
+                "text/x-sexp",
+            ],
+            ContentType::MessagePack => &[
+                "application/vnd.msgpack",
+                "messagepack",
+                "msgpack",
+                "application/msgpack",
+                "application/x-msgpack",
+                "application/messagepack",
+                "application/x-messagepack",
+            ],
+            ContentType::Pickle => &[
+                "application/x-pickle",
+                "pickle",
+                "application/pickle",
+                "application/python-pickle",
+                "application/x-python-pickle",
+            ],
+            ContentType::Postcard => {
+                &["application/x-postcard", "postcard", "application/postcard"]
+            }
+            ContentType::Ron => &["application/ron", "ron", "application/x-ron"],
+            ContentType::Toml => &[
+                "application/toml",
+                "toml",
+                "application/x-toml",
+                "text/toml",
+                "text/x-toml",
+            ],
+            ContentType::Url => &[
+                "application/x-www-form-urlencoded",
+                "url",
+                "urlencoded",
+                "application/url",
+                "application/x-url",
+            ],
+            ContentType::Yaml => &[
+                "application/yaml",
+                "yaml",
+                "yml",
+                "application/x-yaml",
+                "text/yaml",
+                "text/x-yaml",
+            ],
             #[cfg(feature = "accept-limited-xml-serialize")]
-            "application/x-xml" => Ok(ContentType::Xml),
-            _ => Err(Error::UnknownContentTypeMatchFromStr(s.to_string())),
+            ContentType::Xml => &["application/xml", "xml", "application/x-xml", "text/xml"],
+            ContentType::Custom(_) => &[],
         }
     }
 }
 
+/// The built-in formats, JSON first as the default of a wildcard when negotiating.
+pub(crate) const BUILTIN: &[ContentType] = &[
+    ContentType::Json,
+    ContentType::Bson,
+    ContentType::Cbor,
+    ContentType::FlexBuffers,
+    ContentType::Json5,
+    ContentType::JsonLines,
+    ContentType::Lexpr,
+    ContentType::MessagePack,
+    ContentType::Pickle,
+    ContentType::Postcard,
+    ContentType::Ron,
+    ContentType::Toml,
+    ContentType::Url,
+    ContentType::Yaml,
+    #[cfg(feature = "accept-limited-xml-serialize")]
+    ContentType::Xml,
+    ContentType::Avro,
+];
+
 impl TryFrom<String> for ContentType {
     type Error = crate::Error;
 
@@ -259,11 +589,13 @@ impl TryFrom<&ContentType> for ContentType {
 
     fn try_from(h: &ContentType) -> std::result::Result<ContentType, Self::Error> {
         match h {
+            Self::Avro => Ok(Self::Avro),
             Self::Bson => Ok(Self::Bson),
             Self::Cbor => Ok(Self::Cbor),
             Self::FlexBuffers => Ok(Self::FlexBuffers),
             Self::Json => Ok(Self::Json),
             Self::Json5 => Ok(Self::Json5),
+            Self::JsonLines => Ok(Self::JsonLines),
             Self::Lexpr => Ok(Self::Lexpr),
             Self::MessagePack => Ok(Self::MessagePack),
             Self::Pickle => Ok(Self::Pickle),
@@ -274,82 +606,66 @@ impl TryFrom<&ContentType> for ContentType {
             Self::Yaml => Ok(Self::Yaml),
             #[cfg(feature = "accept-limited-xml-serialize")]
             Self::Xml => Ok(Self::Xml),
+            Self::Custom(codec) => Ok(Self::Custom(codec.clone())),
         }
     }
 }
 
-#[cfg(feature = "http")]
+#[cfg(feature = "actix-http")]
 impl TryIntoHeaderValue for ContentType {
-    type Error = http::header::InvalidHeaderValue;
-
-    fn try_into_value(self) -> std::result::Result<HeaderValue, Self::Error> {
-        HeaderValue::from_str(match self {
-            ContentType::Bson => "application/x-bson",
-            ContentType::Cbor => "application/x-cbor",
-            ContentType::FlexBuffers => "application/x-flexbuffers",
-            ContentType::Json => "application/json",
-            ContentType::Json5 => "application/json5",
-            ContentType::Lexpr => "application/x-lexpr",
-            ContentType::MessagePack => "application/x-messagepack",
-            ContentType::Pickle => "application/x-pickle",
-            ContentType::Postcard => "application/x-postcard",
-            ContentType::Ron => "application/ron",
-            ContentType::Toml => "application/toml",
-            ContentType::Url => "application/x-url",
-            ContentType::Yaml => "application/yaml",
-            #[cfg(feature = "accept-limited-xml-serialize")]
-            ContentType::Xml => "application/xml",
-        })
+    type Error = actix_http::header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> std::result::Result<actix_http::header::HeaderValue, Self::Error> {
+        (&self).try_into_value()
     }
 }
-#[cfg(feature = "http")]
+#[cfg(feature = "actix-http")]
 impl TryIntoHeaderValue for &ContentType {
-    type Error = http::header::InvalidHeaderValue;
-
-    fn try_into_value(self) -> std::result::Result<HeaderValue, Self::Error> {
-        HeaderValue::from_str(match self {
-            ContentType::Bson => "application/x-bson",
-            ContentType::Cbor => "application/x-cbor",
-            ContentType::FlexBuffers => "application/x-flexbuffers",
-            ContentType::Json => "application/json",
-            ContentType::Json5 => "application/json5",
-            ContentType::Lexpr => "application/x-lexpr",
-            ContentType::MessagePack => "application/x-messagepack",
-            ContentType::Pickle => "application/x-pickle",
-            ContentType::Postcard => "application/x-postcard",
-            ContentType::Ron => "application/ron",
-            ContentType::Toml => "application/toml",
-            ContentType::Url => "application/x-url",
-            ContentType::Yaml => "application/yaml",
-            #[cfg(feature = "accept-limited-xml-serialize")]
-            ContentType::Xml => "application/xml",
-        })
+    type Error = actix_http::header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> std::result::Result<actix_http::header::HeaderValue, Self::Error> {
+        actix_http::header::HeaderValue::from_str(self.mime())
     }
 }
 
-#[cfg(feature = "http")]
-impl TryFrom<HeaderValue> for ContentType {
+#[cfg(feature = "mime")]
+impl TryFrom<&mime::Mime> for ContentType {
     type Error = Error;
 
-    fn try_from(h: HeaderValue) -> std::result::Result<ContentType, Self::Error> {
-        h.to_str()
-            .map_err(Error::from)
-            .and_then(ContentType::try_from)
+    fn try_from(m: &mime::Mime) -> std::result::Result<ContentType, Self::Error> {
+        ContentType::try_from(m.essence_str())
     }
 }
 
-#[cfg(feature = "http")]
-impl TryFrom<&HeaderValue> for ContentType {
+#[cfg(feature = "mime")]
+impl TryFrom<mime::Mime> for ContentType {
     type Error = Error;
 
-    fn try_from(h: &HeaderValue) -> std::result::Result<ContentType, Self::Error> {
-        h.to_str()
-            .map_err(Error::from)
-            .and_then(ContentType::try_from)
+    fn try_from(m: mime::Mime) -> std::result::Result<ContentType, Self::Error> {
+        ContentType::try_from(&m)
+    }
+}
+
+#[cfg(feature = "mime")]
+impl TryFrom<&ContentType> for mime::Mime {
+    type Error = mime::FromStrError;
+
+    fn try_from(content_type: &ContentType) -> std::result::Result<mime::Mime, Self::Error> {
+        content_type.mime().parse()
+    }
+}
+
+#[cfg(feature = "mime")]
+impl TryFrom<ContentType> for mime::Mime {
+    type Error = mime::FromStrError;
+
+    fn try_from(content_type: ContentType) -> std::result::Result<mime::Mime, Self::Error> {
+        mime::Mime::try_from(&content_type)
     }
 }
 
-#[derive(Debug, Display)]
+/// Backend errors that are not `Clone` are held in an `Arc`, so cloning an `Error` is cheap.
+#[derive(Clone, Debug, Display)]
 pub enum Error {
     #[display(fmt = "Infallible - This error should have been infallible")]
     Infallible,
@@ -357,28 +673,36 @@ pub enum Error {
     ByteToUTF8ConversionFailure(Utf8Error),
     #[display(fmt = "Unknown content type match from str: {}", _0)]
     UnknownContentTypeMatchFromStr(String),
+    #[display(fmt = "IO error while reading/writing: {}", _0)]
+    IoError(Arc<std::io::Error>),
+    #[display(fmt = "Avro encoder/decoder error: {}", _0)]
+    AvroError(Arc<avro_rs::Error>),
+    #[display(fmt = "No Avro schema has been registered for: {}", _0)]
+    AvroSchemaNotRegistered(String),
+    #[display(fmt = "Expected a single Avro record, found: {}", _0)]
+    AvroUnexpectedRecordCount(usize),
     #[display(fmt = "BSON encoder/decoder error: {}", _0)]
     BsonSerializationFailure(bson::ser::Error),
     #[display(fmt = "BSON encode/decoder error: {}", _0)]
-    BsonDeserializationFailure(bson::de::Error),
+    BsonDeserializationFailure(Arc<bson::de::Error>),
     #[display(fmt = "CBOR encoder/decoder error: {}", _0)]
-    CborFailure(serde_cbor::Error),
+    CborFailure(Arc<serde_cbor::Error>),
     #[display(fmt = "Flexbuffers encoder/decoder error: {}", _0)]
-    FlexBuffersSerializationFailure(flexbuffers::SerializationError),
+    FlexBuffersSerializationFailure(Arc<flexbuffers::SerializationError>),
     #[display(fmt = "Flexbuffers encoder/decoder error: {}", _0)]
     FlexBuffersDeserializationFailure(flexbuffers::DeserializationError),
     #[display(fmt = "JSON encoder/decoder error: {}", _0)]
-    JsonError(serde_json::Error),
+    JsonError(Arc<serde_json::Error>),
     #[display(fmt = "JSON5 encoder/decoder error: {}", _0)]
     Json5Error(json5::Error),
     #[display(fmt = "LEXPR encoder/decoder error: {}", _0)]
-    LexprError(serde_lexpr::Error),
+    LexprError(Arc<serde_lexpr::Error>),
     #[display(fmt = "MessagePack encoder/decoder error: {}", _0)]
-    MessagePackEncodeError(rmp_serde::encode::Error),
+    MessagePackEncodeError(Arc<rmp_serde::encode::Error>),
     #[display(fmt = "MessagePack encoder/decoder error: {}", _0)]
-    MessagePackDecodeError(rmp_serde::decode::Error),
+    MessagePackDecodeError(Arc<rmp_serde::decode::Error>),
     #[display(fmt = "Pickle encoder/decoder error: {}", _0)]
-    PickleError(serde_pickle::Error),
+    PickleError(Arc<serde_pickle::Error>),
     #[display(fmt = "Postcard encoder/decoder error: {}", _0)]
     PostcardError(postcard::Error),
     #[display(fmt = "RON encoder/decoder error: {}", _0)]
@@ -390,33 +714,63 @@ pub enum Error {
     #[display(fmt = "TOML encoder/decoder error: {}", _0)]
     TomlDeserializationFailure(toml::de::Error),
     #[display(fmt = "URL encoder/decoder error: {}", _0)]
-    UrlEncodingFailure(serde_qs::Error),
+    UrlEncodingFailure(Arc<serde_qs::Error>),
     #[display(fmt = "YAML encoder/decoder error: {}", _0)]
-    YamlError(serde_yaml::Error),
+    YamlError(Arc<serde_yaml::Error>),
     #[display(fmt = "XML encoder/decoder error: {}", _0)]
     #[cfg(feature = "accept-limited-xml-serialize")]
-    XmlError(prelude::xml::Error),
+    XmlError(Arc<prelude::xml::Error>),
+    #[display(fmt = "Custom codec encoder/decoder error: {}", _0)]
+    CodecFailure(Arc<erased_serde::Error>),
+    #[display(fmt = "{}", _0)]
+    EncodeFailure(Box<EncodeError>),
+    #[display(fmt = "{}", _0)]
+    DecodeFailure(Box<DecodeError>),
+    #[display(fmt = "{}", _0)]
+    RecordFailure(Box<RecordError>),
+    #[display(fmt = "{:?} does not support the option: {}", _0, _1)]
+    UnsupportedOption(ContentType, String),
+    #[display(fmt = "Invalid media type: {}", _0)]
+    InvalidMediaType(String),
+    #[display(fmt = "{:?} does not support the charset: {}", _0, _1)]
+    UnsupportedCharset(ContentType, String),
+    #[display(fmt = "The {} limit of {} was exceeded", kind, limit)]
+    LimitExceeded { kind: LimitKind, limit: usize },
+    #[display(fmt = "Unable to detect the content type")]
+    ContentTypeNotDetected,
+    #[display(fmt = "No format is known for the extension of: {}", _0)]
+    UnknownExtension(String),
+    #[display(fmt = "None of the accepted content types are supported: {}", _0)]
+    NotAcceptable(String),
     #[display(fmt = "Type is not supported for encoding/decoding: {:?}", _0)]
     TypeDoesNotSupportSerialization(ContentType),
+    #[display(fmt = "Type does not support borrowed decoding: {:?}", _0)]
+    BorrowedDecodingNotSupported(ContentType),
+    #[display(fmt = "Type does not support transcoding: {:?}", _0)]
+    TranscodingNotSupported(ContentType),
+    #[display(fmt = "Type does not support record streams: {:?}", _0)]
+    StreamingNotSupported(ContentType),
+    #[display(fmt = "Invalid {:?} frame: {}", _0, _1)]
+    InvalidFrame(ContentType, String),
     #[cfg(feature = "http")]
     #[display(fmt = "Failed to convert `HeaderValue` to a ContentType: {}", _0)]
-    FailedConvertingHeaderValueToContentType(http::header::ToStrError),
+    FailedConvertingHeaderValueToContentType(Arc<http::header::ToStrError>),
     #[cfg(feature = "http")]
     #[display(fmt = "Invalid Header Value found: {}", _0)]
-    InvalidHeaderValue(http::header::InvalidHeaderValue),
+    InvalidHeaderValue(Arc<http::header::InvalidHeaderValue>),
 }
 
 #[cfg(feature = "http")]
 impl From<http::header::InvalidHeaderValue> for Error {
     fn from(e: http::header::InvalidHeaderValue) -> Self {
-        Self::InvalidHeaderValue(e)
+        Self::InvalidHeaderValue(Arc::new(e))
     }
 }
 
 #[cfg(feature = "http")]
 impl From<http::header::ToStrError> for Error {
-    fn from(e: ToStrError) -> Self {
-        Self::FailedConvertingHeaderValueToContentType(e)
+    fn from(e: http::header::ToStrError) -> Self {
+        Self::FailedConvertingHeaderValueToContentType(Arc::new(e))
     }
 }
 
@@ -436,6 +790,21 @@ impl From<Utf8Error> for Error {
         Error::ByteToUTF8ConversionFailure(e)
     }
 }
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IoError(Arc::new(e))
+    }
+}
+impl From<avro_rs::Error> for Error {
+    fn from(e: avro_rs::Error) -> Self {
+        Error::AvroError(Arc::new(e))
+    }
+}
+impl From<erased_serde::Error> for Error {
+    fn from(e: erased_serde::Error) -> Self {
+        Error::CodecFailure(Arc::new(e))
+    }
+}
 impl From<bson::ser::Error> for Error {
     fn from(e: bson::ser::Error) -> Self {
         Error::BsonSerializationFailure(e)
@@ -443,17 +812,17 @@ impl From<bson::ser::Error> for Error {
 }
 impl From<bson::de::Error> for Error {
     fn from(e: bson::de::Error) -> Self {
-        Error::BsonDeserializationFailure(e)
+        Error::BsonDeserializationFailure(Arc::new(e))
     }
 }
 impl From<serde_cbor::Error> for Error {
     fn from(e: serde_cbor::Error) -> Self {
-        Error::CborFailure(e)
+        Error::CborFailure(Arc::new(e))
     }
 }
 impl From<flexbuffers::SerializationError> for Error {
     fn from(e: flexbuffers::SerializationError) -> Self {
-        Error::FlexBuffersSerializationFailure(e)
+        Error::FlexBuffersSerializationFailure(Arc::new(e))
     }
 }
 impl From<flexbuffers::DeserializationError> for Error {
@@ -463,7 +832,7 @@ impl From<flexbuffers::DeserializationError> for Error {
 }
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
-        Error::JsonError(e)
+        Error::JsonError(Arc::new(e))
     }
 }
 impl From<json5::Error> for Error {
@@ -473,22 +842,22 @@ impl From<json5::Error> for Error {
 }
 impl From<serde_lexpr::Error> for Error {
     fn from(e: serde_lexpr::Error) -> Self {
-        Error::LexprError(e)
+        Error::LexprError(Arc::new(e))
     }
 }
 impl From<rmp_serde::encode::Error> for Error {
     fn from(e: rmp_serde::encode::Error) -> Self {
-        Error::MessagePackEncodeError(e)
+        Error::MessagePackEncodeError(Arc::new(e))
     }
 }
 impl From<rmp_serde::decode::Error> for Error {
     fn from(e: rmp_serde::decode::Error) -> Self {
-        Error::MessagePackDecodeError(e)
+        Error::MessagePackDecodeError(Arc::new(e))
     }
 }
 impl From<serde_pickle::Error> for Error {
     fn from(e: serde_pickle::Error) -> Self {
-        Error::PickleError(e)
+        Error::PickleError(Arc::new(e))
     }
 }
 impl From<postcard::Error> for Error {
@@ -513,18 +882,18 @@ impl From<toml::de::Error> for Error {
 }
 impl From<serde_qs::Error> for Error {
     fn from(e: serde_qs::Error) -> Self {
-        Error::UrlEncodingFailure(e)
+        Error::UrlEncodingFailure(Arc::new(e))
     }
 }
 impl From<serde_yaml::Error> for Error {
     fn from(e: serde_yaml::Error) -> Self {
-        Error::YamlError(e)
+        Error::YamlError(Arc::new(e))
     }
 }
 #[cfg(feature = "accept-limited-xml-serialize")]
 impl From<prelude::xml::Error> for Error {
     fn from(e: prelude::xml::Error) -> Self {
-        Error::XmlError(e)
+        Error::XmlError(Arc::new(e))
     }
 }
 impl From<ron::de::SpannedError> for Error {
@@ -533,6 +902,46 @@ impl From<ron::de::SpannedError> for Error {
     }
 }
 
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ByteToUTF8ConversionFailure(e) => Some(e),
+            Error::IoError(e) => Some(&**e),
+            Error::AvroError(e) => Some(&**e),
+            Error::BsonSerializationFailure(e) => Some(e),
+            Error::BsonDeserializationFailure(e) => Some(&**e),
+            Error::CborFailure(e) => Some(&**e),
+            Error::FlexBuffersSerializationFailure(e) => Some(&**e),
+            Error::FlexBuffersDeserializationFailure(e) => Some(e),
+            Error::JsonError(e) => Some(&**e),
+            Error::Json5Error(e) => Some(e),
+            Error::LexprError(e) => Some(&**e),
+            Error::MessagePackEncodeError(e) => Some(&**e),
+            Error::MessagePackDecodeError(e) => Some(&**e),
+            Error::PickleError(e) => Some(&**e),
+            Error::PostcardError(e) => Some(e),
+            Error::RonError(e) => Some(e),
+            Error::RonDecodeError(e) => Some(e),
+            Error::TomlSerializationFailure(e) => Some(e),
+            Error::TomlDeserializationFailure(e) => Some(e),
+            Error::UrlEncodingFailure(e) => Some(&**e),
+            Error::YamlError(e) => Some(&**e),
+            #[cfg(feature = "accept-limited-xml-serialize")]
+            Error::XmlError(e) => Some(&**e),
+            Error::CodecFailure(e) => Some(&**e),
+            // the backend error, not the `EncodeError` or `DecodeError` repeating it
+            Error::EncodeFailure(e) => Some(e.error()),
+            Error::DecodeFailure(e) => Some(e.error()),
+            Error::RecordFailure(e) => Some(e.error()),
+            #[cfg(feature = "http")]
+            Error::FailedConvertingHeaderValueToContentType(e) => Some(&**e),
+            #[cfg(feature = "http")]
+            Error::InvalidHeaderValue(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
 pub trait TryToString {
     type Error;
     fn try_to_string(&self) -> std::result::Result<String, Self::Error>;
@@ -546,6 +955,21 @@ where
         &self,
         content_type: F,
     ) -> Result<Encoded>;
+
+    /// Encode using `options` to configure the backend, see `options` module documentation.
+    fn encode_with<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &self,
+        content_type: F,
+        options: &EncodeOptions,
+    ) -> Result<Encoded>;
+
+    /// Encode directly into `writer`, see the module documentation for the formats that are
+    /// buffered before being written.
+    fn encode_to_writer<W: Write, F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &self,
+        writer: &mut W,
+        content_type: F,
+    ) -> Result<()>;
 }
 
 impl<T> SimpleEncoder for T
@@ -556,28 +980,87 @@ where
         &self,
         content_type: F,
     ) -> Result<Encoded> {
+        self.encode_with(content_type, &EncodeOptions::default())
+    }
+
+    fn encode_with<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &self,
+        content_type: F,
+        options: &EncodeOptions,
+    ) -> Result<Encoded> {
+        let avro = |o: &T| -> Result<Encoded> { avro::encode(o).try_into() };
         let bson = |o: &T| -> Result<Encoded> { bson::to_vec(o).try_into() };
         let cbor = |o: &T| -> Result<Encoded> { serde_cbor::to_vec(o).try_into() };
         let flexbuffers = |o: &T| -> Result<Encoded> { flexbuffers::to_vec(o).try_into() };
-        let json = |o: &T| -> Result<Encoded> { serde_json::to_vec(o).try_into() };
+        let json = |o: &T| -> Result<Encoded> {
+            match options.json_indent() {
+                Some(indent) => {
+                    let formatter = serde_json::ser::PrettyFormatter::with_indent(&indent);
+                    let mut serializer =
+                        serde_json::Serializer::with_formatter(Vec::new(), formatter);
+                    o.serialize(&mut serializer)
+                        .map(|_| serializer.into_inner())
+                        .try_into()
+                }
+                None => serde_json::to_vec(o).try_into(),
+            }
+        };
         let json5 = |o: &T| -> Result<Encoded> { json5::to_string(o).try_into() };
+        let json_lines = |o: &T| -> Result<Encoded> {
+            let mut line = serde_json::to_vec(o)?;
+            line.push(b'\n');
+            Ok(line.into())
+        };
         let lexpr = |o: &T| -> Result<Encoded> { serde_lexpr::to_vec(o).try_into() };
-        let message_pack = |o: &T| -> Result<Encoded> { rmp_serde::to_vec(o).try_into() };
-        let pickle =
-            |o: &T| -> Result<Encoded> { serde_pickle::to_vec(o, Default::default()).try_into() };
+        let message_pack = |o: &T| -> Result<Encoded> {
+            match options.message_pack_struct_map {
+                true => rmp_serde::to_vec_named(o).try_into(),
+                false => rmp_serde::to_vec(o).try_into(),
+            }
+        };
+        let pickle = |o: &T| -> Result<Encoded> {
+            serde_pickle::to_vec(o, options.pickle_options()?).try_into()
+        };
         let postcard = |o: &T| -> Result<Encoded> { postcard::to_allocvec(o).try_into() };
-        let ron = |o: &T| -> Result<Encoded> { ron::to_string(o).try_into() };
-        let toml = |o: &T| -> Result<Encoded> { toml::to_vec(o).try_into() };
+        let ron = |o: &T| -> Result<Encoded> {
+            match options.ron_config() {
+                Some(config) => ron::ser::to_string_pretty(o, config).try_into(),
+                None => ron::to_string(o).try_into(),
+            }
+        };
+        let toml = |o: &T| -> Result<Encoded> {
+            let mut output = String::new();
+            let mut serializer = match options.pretty {
+                true => toml::Serializer::pretty(&mut output),
+                false => toml::Serializer::new(&mut output),
+            };
+            if options.pretty_arrays {
+                serializer.pretty_array(true);
+            }
+            // setting the indent makes arrays multi-line, like the other formats it only applies
+            // when pretty printing
+            match options.indent {
+                Some(indent) if options.pretty || options.pretty_arrays => {
+                    serializer.pretty_array_indent(indent);
+                }
+                _ => {}
+            }
+            o.serialize(&mut serializer)?;
+            Ok(output.into())
+        };
         let url = |o: &T| -> Result<Encoded> { serde_qs::to_string(o).try_into() };
         let yaml = |o: &T| -> Result<Encoded> { serde_yaml::to_string(o).try_into() };
         #[cfg(feature = "accept-limited-xml-serialize")]
         let xml = |o: &T| -> Result<Encoded> { prelude::xml::to_string(o).try_into() };
-        match content_type.try_into().map_err(|e| e.into())? {
+        let content_type = content_type.try_into().map_err(|e| e.into())?;
+        let encoded = match &content_type {
+            ContentType::Avro => avro(self),
             ContentType::Bson => bson(self),
             ContentType::Cbor => cbor(self),
             ContentType::FlexBuffers => flexbuffers(self),
             ContentType::Json => json(self),
             ContentType::Json5 => json5(self),
+            ContentType::JsonLines => json_lines(self),
             ContentType::Lexpr => lexpr(self),
             ContentType::MessagePack => message_pack(self),
             ContentType::Pickle => pickle(self),
@@ -588,6 +1071,212 @@ where
             ContentType::Yaml => yaml(self),
             #[cfg(feature = "accept-limited-xml-serialize")]
             ContentType::Xml => xml(self),
+            ContentType::Custom(codec) => codec::encode(codec.as_ref(), self).try_into(),
+        };
+        match encoded {
+            Ok(encoded) => Ok(Encoded {
+                content_type: Some(content_type),
+                options: Some(options.clone()),
+                ..encoded
+            }),
+            Err(e) => Err(kind::encode_failure::<T>(&content_type, e)),
+        }
+    }
+
+    fn encode_to_writer<W: Write, F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &self,
+        writer: &mut W,
+        content_type: F,
+    ) -> Result<()> {
+        let buffered = |o: &T, w: &mut W, c: ContentType| -> Result<()> {
+            o.encode(c)
+                .and_then(|e| w.write_all(&e).map_err(Error::from))
+        };
+        let avro = |o: &T, w: &mut W| -> Result<()> { avro::encode_to_writer(w, o).map(|_| ()) };
+        let cbor =
+            |o: &T, w: &mut W| -> Result<()> { serde_cbor::to_writer(w, o).map_err(Error::from) };
+        let json =
+            |o: &T, w: &mut W| -> Result<()> { serde_json::to_writer(w, o).map_err(Error::from) };
+        let json_lines = |o: &T, w: &mut W| -> Result<()> {
+            serde_json::to_writer(&mut *w, o)?;
+            w.write_all(b"\n").map_err(Error::from)
+        };
+        let lexpr =
+            |o: &T, w: &mut W| -> Result<()> { serde_lexpr::to_writer(w, o).map_err(Error::from) };
+        let message_pack = |o: &T, w: &mut W| -> Result<()> {
+            rmp_serde::encode::write(w, o).map_err(Error::from)
+        };
+        let pickle = |o: &T, w: &mut W| -> Result<()> {
+            serde_pickle::to_writer(w, o, Default::default()).map_err(Error::from)
+        };
+        let ron =
+            |o: &T, w: &mut W| -> Result<()> { ron::ser::to_writer(w, o).map_err(Error::from) };
+        let url =
+            |o: &T, w: &mut W| -> Result<()> { serde_qs::to_writer(o, w).map_err(Error::from) };
+        let yaml =
+            |o: &T, w: &mut W| -> Result<()> { serde_yaml::to_writer(w, o).map_err(Error::from) };
+        #[cfg(feature = "accept-limited-xml-serialize")]
+        let xml =
+            |o: &T, w: &mut W| -> Result<()> { prelude::xml::to_writer(w, o).map_err(Error::from) };
+        let content_type = content_type.try_into().map_err(|e| e.into())?;
+        let encoded = match content_type.clone() {
+            ContentType::Avro => avro(self, writer),
+            ContentType::Bson => buffered(self, writer, ContentType::Bson),
+            ContentType::Cbor => cbor(self, writer),
+            ContentType::FlexBuffers => buffered(self, writer, ContentType::FlexBuffers),
+            ContentType::Json => json(self, writer),
+            ContentType::Json5 => buffered(self, writer, ContentType::Json5),
+            ContentType::JsonLines => json_lines(self, writer),
+            ContentType::Lexpr => lexpr(self, writer),
+            ContentType::MessagePack => message_pack(self, writer),
+            ContentType::Pickle => pickle(self, writer),
+            ContentType::Postcard => buffered(self, writer, ContentType::Postcard),
+            ContentType::Ron => ron(self, writer),
+            ContentType::Toml => buffered(self, writer, ContentType::Toml),
+            ContentType::Url => url(self, writer),
+            ContentType::Yaml => yaml(self, writer),
+            #[cfg(feature = "accept-limited-xml-serialize")]
+            ContentType::Xml => xml(self, writer),
+            custom @ ContentType::Custom(_) => buffered(self, writer, custom),
+        };
+        encoded.map_err(|e| kind::encode_failure::<T>(&content_type, e))
+    }
+}
+
+/// Decode directly from `reader`, see the module documentation for the formats that are read into
+/// an internal buffer before being decoded.
+pub fn decode_from_reader<T, R, F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+    reader: R,
+    content_type: F,
+) -> Result<Decoded<T>>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    let content_type = content_type.try_into().map_err(|e| e.into())?;
+    guard::decode(&content_type, &Limits::default(), || {
+        read::<Guarded<T>, T, R>(reader, &content_type).map(Decoded::into)
+    })
+    .map(Decoded::from)
+}
+
+/// Decode from `reader` with the backend of `content_type`, `S` is the type the Avro schema is
+/// registered for.
+fn read<T, S: ?Sized, R>(reader: R, content_type: &ContentType) -> Result<Decoded<T>>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    let buffered = |mut r: R, c: &ContentType| -> Result<Decoded<T>> {
+        let mut buffer = Vec::new();
+        r.read_to_end(&mut buffer)?;
+        decode_slice::<T, S>(&buffer, c, &DecodeOptions::default())
+    };
+    let avro = |r: R| -> Result<Decoded<T>> { avro::decode_from_reader::<S, R, T>(r).try_into() };
+    let bson = |r: R| -> Result<Decoded<T>> { bson::from_reader(r).try_into() };
+    let cbor = |r: R| -> Result<Decoded<T>> { serde_cbor::from_reader(r).try_into() };
+    let json = |r: R| -> Result<Decoded<T>> { serde_json::from_reader(r).try_into() };
+    let lexpr = |r: R| -> Result<Decoded<T>> { serde_lexpr::from_reader(r).try_into() };
+    let message_pack = |r: R| -> Result<Decoded<T>> { rmp_serde::from_read(r).try_into() };
+    let pickle = |r: R| -> Result<Decoded<T>> {
+        serde_pickle::from_reader(r, Default::default()).try_into()
+    };
+    #[cfg(feature = "accept-limited-xml-serialize")]
+    let xml = |r: R| -> Result<Decoded<T>> { prelude::xml::from_reader(r).try_into() };
+    match content_type {
+        ContentType::Avro => avro(reader),
+        ContentType::Bson => bson(reader),
+        ContentType::Cbor => cbor(reader),
+        ContentType::FlexBuffers => buffered(reader, content_type),
+        ContentType::Json => json(reader),
+        ContentType::Json5 => buffered(reader, content_type),
+        ContentType::JsonLines => json(reader),
+        ContentType::Lexpr => lexpr(reader),
+        ContentType::MessagePack => message_pack(reader),
+        ContentType::Pickle => pickle(reader),
+        ContentType::Postcard => buffered(reader, content_type),
+        ContentType::Ron => buffered(reader, content_type),
+        ContentType::Toml => buffered(reader, content_type),
+        ContentType::Url => buffered(reader, content_type),
+        ContentType::Yaml => buffered(reader, content_type),
+        #[cfg(feature = "accept-limited-xml-serialize")]
+        ContentType::Xml => xml(reader),
+        ContentType::Custom(_) => buffered(reader, content_type),
+    }
+}
+
+/// Decode `data` with the backend of `content_type`, `S` is the type the Avro schema is registered
+/// for.
+fn decode_slice<T: DeserializeOwned, S: ?Sized>(
+    data: &[u8],
+    content_type: &ContentType,
+    options: &DecodeOptions,
+) -> Result<Decoded<T>> {
+    let avro = |o: &[u8]| -> Result<Decoded<T>> { avro::decode::<S, T>(o).try_into() };
+    let bson = |o: &[u8]| -> Result<Decoded<T>> { bson::from_slice(o).try_into() };
+    let cbor = |o: &[u8]| -> Result<Decoded<T>> { serde_cbor::from_slice(o).try_into() };
+    let flexbuffers = |o: &[u8]| -> Result<Decoded<T>> { flexbuffers::from_slice(o).try_into() };
+    let json = |o: &[u8]| -> Result<Decoded<T>> { serde_json::from_slice(o).try_into() };
+    let json5 = |o: &[u8]| -> Result<Decoded<T>> {
+        std::str::from_utf8(o)
+            .map_err(Error::from)
+            .and_then(|str| json5::from_str(str).try_into())
+    };
+    let lexpr = |o: &[u8]| -> Result<Decoded<T>> { serde_lexpr::from_slice(o).try_into() };
+    let message_pack = |o: &[u8]| -> Result<Decoded<T>> {
+        let mut de = rmp_serde::Deserializer::from_read_ref(o);
+        if let Some(depth) = options.recursion_limit {
+            // rmp-serde fails when reaching the limit, rather than when exceeding it
+            de.set_max_depth(depth.saturating_add(1));
+        }
+        T::deserialize(&mut de).try_into()
+    };
+    let pickle = |o: &[u8]| -> Result<Decoded<T>> {
+        let pickle_options = options.pickle_options.clone().unwrap_or_default();
+        serde_pickle::from_slice(o, pickle_options).try_into()
+    };
+    let postcard = |o: &[u8]| -> Result<Decoded<T>> { postcard::from_bytes(o).try_into() };
+    let ron = |o: &[u8]| -> Result<Decoded<T>> {
+        let extensions = options
+            .ron_extensions
+            .unwrap_or_else(ron::extensions::Extensions::empty);
+        std::str::from_utf8(o).map_err(Error::from).and_then(|str| {
+            ron::Options::default()
+                .with_default_extension(extensions)
+                .from_str(str)
+                .try_into()
+        })
+    };
+    let toml = |o: &[u8]| -> Result<Decoded<T>> { toml::from_slice(o).try_into() };
+    let url =
+        |o: &[u8]| -> Result<Decoded<T>> { options.url_config().deserialize_bytes(o).try_into() };
+    let yaml = |o: &[u8]| -> Result<Decoded<T>> { serde_yaml::from_slice(o).try_into() };
+    #[cfg(feature = "accept-limited-xml-serialize")]
+    let xml = |o: &[u8]| -> Result<Decoded<T>> {
+        std::str::from_utf8(o)
+            .map_err(Error::from)
+            .and_then(|str| prelude::xml::de::from_str(str).try_into())
+    };
+    match content_type {
+        ContentType::Avro => avro(data),
+        ContentType::Bson => bson(data),
+        ContentType::Cbor => cbor(data),
+        ContentType::FlexBuffers => flexbuffers(data),
+        ContentType::Json => json(data),
+        ContentType::Json5 => json5(data),
+        ContentType::JsonLines => json(data),
+        ContentType::Lexpr => lexpr(data),
+        ContentType::MessagePack => message_pack(data),
+        ContentType::Pickle => pickle(data),
+        ContentType::Postcard => postcard(data),
+        ContentType::Ron => ron(data),
+        ContentType::Toml => toml(data),
+        ContentType::Url => url(data),
+        ContentType::Yaml => yaml(data),
+        #[cfg(feature = "accept-limited-xml-serialize")]
+        ContentType::Xml => xml(data),
+        ContentType::Custom(codec) => {
+            codec::decode(codec.as_ref(), data, options.media_type.as_ref()).try_into()
         }
     }
 }
@@ -597,6 +1286,18 @@ pub trait SimpleDecoder<T> {
         &self,
         content_type: F,
     ) -> Result<T>;
+
+    /// Decode using `options` to configure the backend, see `options` module documentation.
+    fn decode_with<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &self,
+        content_type: F,
+        options: &DecodeOptions,
+    ) -> Result<T>;
+
+    /// Decode using the candidates of `ContentType::detect_all`, the first candidate that decodes
+    /// successfully is used. If none of them does, the error of the most likely candidate is
+    /// returned.
+    fn decode_auto(&self) -> Result<T>;
 }
 
 impl<T> SimpleDecoder<Decoded<T>> for &[u8]
@@ -607,53 +1308,34 @@ where
         &self,
         content_type: F,
     ) -> Result<Decoded<T>> {
-        let bson = |o: &[u8]| -> Result<Decoded<T>> { bson::from_slice(o).try_into() };
-        let cbor = |o: &[u8]| -> Result<Decoded<T>> { serde_cbor::from_slice(o).try_into() };
-        let flexbuffers =
-            |o: &[u8]| -> Result<Decoded<T>> { flexbuffers::from_slice(o).try_into() };
-        let json = |o: &[u8]| -> Result<Decoded<T>> { serde_json::from_slice(o).try_into() };
-        let json5 = |o: &[u8]| -> Result<Decoded<T>> {
-            std::str::from_utf8(o)
-                .map_err(Error::from)
-                .and_then(|str| json5::from_str(str).try_into())
-        };
-        let lexpr = |o: &[u8]| -> Result<Decoded<T>> { serde_lexpr::from_slice(o).try_into() };
-        let message_pack = |o: &[u8]| -> Result<Decoded<T>> { rmp_serde::from_slice(o).try_into() };
-        let pickle = |o: &[u8]| -> Result<Decoded<T>> {
-            serde_pickle::from_slice(o, Default::default()).try_into()
-        };
-        let postcard = |o: &[u8]| -> Result<Decoded<T>> { postcard::from_bytes(o).try_into() };
-        let ron = |o: &[u8]| -> Result<Decoded<T>> {
-            std::str::from_utf8(o)
-                .map_err(Error::from)
-                .and_then(|str| ron::from_str(str).try_into())
-        };
-        let toml = |o: &[u8]| -> Result<Decoded<T>> { toml::from_slice(o).try_into() };
-        let url = |o: &[u8]| -> Result<Decoded<T>> { serde_qs::from_bytes(o).try_into() };
-        let yaml = |o: &[u8]| -> Result<Decoded<T>> { serde_yaml::from_slice(o).try_into() };
-        #[cfg(feature = "accept-limited-xml-serialize")]
-        let xml = |o: &[u8]| -> Result<Decoded<T>> {
-            std::str::from_utf8(o)
-                .map_err(Error::from)
-                .and_then(|str| prelude::xml::de::from_str(str).try_into())
-        };
-        match content_type.try_into().map_err(|e| e.into())? {
-            ContentType::Bson => bson(self),
-            ContentType::Cbor => cbor(self),
-            ContentType::FlexBuffers => flexbuffers(self),
-            ContentType::Json => json(self),
-            ContentType::Json5 => json5(self),
-            ContentType::Lexpr => lexpr(self),
-            ContentType::MessagePack => message_pack(self),
-            ContentType::Pickle => pickle(self),
-            ContentType::Postcard => postcard(self),
-            ContentType::Ron => ron(self),
-            ContentType::Toml => toml(self),
-            ContentType::Url => url(self),
-            ContentType::Yaml => yaml(self),
-            #[cfg(feature = "accept-limited-xml-serialize")]
-            ContentType::Xml => xml(self),
+        self.decode_with(content_type, &DecodeOptions::default())
+    }
+
+    fn decode_with<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &self,
+        content_type: F,
+        options: &DecodeOptions,
+    ) -> Result<Decoded<T>> {
+        let content_type = content_type.try_into().map_err(|e| e.into())?;
+        options.check(&content_type)?;
+        options.limits.check_input(self.len())?;
+        guard::decode(&content_type, &options.limits, || {
+            decode_slice::<Guarded<T>, T>(self, &content_type, options).map(Decoded::into)
+        })
+        .map(Decoded::from)
+    }
+
+    fn decode_auto(&self) -> Result<Decoded<T>> {
+        let mut error = None;
+        for (content_type, _) in ContentType::detect_all(self) {
+            match self.decode(content_type) {
+                Ok(decoded) => return Ok(decoded),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
         }
+        Err(error.unwrap_or(Error::ContentTypeNotDetected))
     }
 }
 
@@ -667,6 +1349,18 @@ where
     ) -> Result<Decoded<T>> {
         self.as_slice().decode(content_type)
     }
+
+    fn decode_with<F: TryInto<ContentType, Error = impl Into<Error>>>(
+        &self,
+        content_type: F,
+        options: &DecodeOptions,
+    ) -> Result<Decoded<T>> {
+        self.as_slice().decode_with(content_type, options)
+    }
+
+    fn decode_auto(&self) -> Result<Decoded<T>> {
+        self.as_slice().decode_auto()
+    }
 }
 
 impl<T> SimpleDecoder<Decoded<T>> for &str
@@ -679,6 +1373,18 @@ where
     ) -> Result<Decoded<T>> {
         self.as_bytes().decode(content_type)
     }
+
+    fn decode_with<F: TryInto<ContentType, Error = impl Into<Error>>>(
+        &self,
+        content_type: F,
+        options: &DecodeOptions,
+    ) -> Result<Decoded<T>> {
+        self.as_bytes().decode_with(content_type, options)
+    }
+
+    fn decode_auto(&self) -> Result<Decoded<T>> {
+        self.as_bytes().decode_auto()
+    }
 }
 
 impl<T> SimpleDecoder<Decoded<T>> for String
@@ -691,10 +1397,151 @@ where
     ) -> Result<Decoded<T>> {
         self.as_bytes().decode(content_type)
     }
+
+    fn decode_with<F: TryInto<ContentType, Error = impl Into<Error>>>(
+        &self,
+        content_type: F,
+        options: &DecodeOptions,
+    ) -> Result<Decoded<T>> {
+        self.as_bytes().decode_with(content_type, options)
+    }
+
+    fn decode_auto(&self) -> Result<Decoded<T>> {
+        self.as_bytes().decode_auto()
+    }
 }
 
+/// Decoding into types borrowing from the data being decoded, see the module documentation for
+/// the supported formats.
+pub trait SimpleBorrowedDecoder<'de, T> {
+    fn decode_borrowed<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &'de self,
+        content_type: F,
+    ) -> Result<T>;
+}
+
+impl<'de, T> SimpleBorrowedDecoder<'de, Decoded<T>> for [u8]
+where
+    T: Deserialize<'de>,
+{
+    fn decode_borrowed<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &'de self,
+        content_type: F,
+    ) -> Result<Decoded<T>> {
+        let content_type = content_type.try_into().map_err(|e| e.into())?;
+        guard::decode(&content_type, &Limits::default(), || {
+            decode_borrowed_slice::<Guarded<T>>(self, &content_type).map(Decoded::into)
+        })
+        .map(Decoded::from)
+    }
+}
+
+/// Decode `data` with the backend of `content_type`, borrowing from `data`.
+fn decode_borrowed_slice<'de, T>(data: &'de [u8], content_type: &ContentType) -> Result<Decoded<T>>
+where
+    T: Deserialize<'de>,
+{
+    let bson = |o: &'de [u8]| -> Result<Decoded<T>> { bson::from_slice(o).try_into() };
+    let cbor = |o: &'de [u8]| -> Result<Decoded<T>> { serde_cbor::from_slice(o).try_into() };
+    let flexbuffers =
+        |o: &'de [u8]| -> Result<Decoded<T>> { flexbuffers::from_slice(o).try_into() };
+    let json = |o: &'de [u8]| -> Result<Decoded<T>> { serde_json::from_slice(o).try_into() };
+    let message_pack = |o: &'de [u8]| -> Result<Decoded<T>> { rmp_serde::from_slice(o).try_into() };
+    let postcard = |o: &'de [u8]| -> Result<Decoded<T>> { postcard::from_bytes(o).try_into() };
+    let ron = |o: &'de [u8]| -> Result<Decoded<T>> {
+        std::str::from_utf8(o)
+            .map_err(Error::from)
+            .and_then(|str| ron::from_str(str).try_into())
+    };
+    let toml = |o: &'de [u8]| -> Result<Decoded<T>> { toml::from_slice(o).try_into() };
+    let url = |o: &'de [u8]| -> Result<Decoded<T>> { serde_qs::from_bytes(o).try_into() };
+    let yaml = |o: &'de [u8]| -> Result<Decoded<T>> { serde_yaml::from_slice(o).try_into() };
+    match content_type {
+        ContentType::Bson => bson(data),
+        ContentType::Cbor => cbor(data),
+        ContentType::FlexBuffers => flexbuffers(data),
+        ContentType::Json | ContentType::JsonLines => json(data),
+        ContentType::MessagePack => message_pack(data),
+        ContentType::Postcard => postcard(data),
+        ContentType::Ron => ron(data),
+        ContentType::Toml => toml(data),
+        ContentType::Url => url(data),
+        ContentType::Yaml => yaml(data),
+        unsupported => Err(Error::BorrowedDecodingNotSupported(unsupported.clone())),
+    }
+}
+
+impl<'de, T> SimpleBorrowedDecoder<'de, Decoded<T>> for Vec<u8>
+where
+    T: Deserialize<'de>,
+{
+    fn decode_borrowed<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &'de self,
+        content_type: F,
+    ) -> Result<Decoded<T>> {
+        self.as_slice().decode_borrowed(content_type)
+    }
+}
+
+impl<'de, T> SimpleBorrowedDecoder<'de, Decoded<T>> for str
+where
+    T: Deserialize<'de>,
+{
+    fn decode_borrowed<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &'de self,
+        content_type: F,
+    ) -> Result<Decoded<T>> {
+        self.as_bytes().decode_borrowed(content_type)
+    }
+}
+
+impl<'de, T> SimpleBorrowedDecoder<'de, Decoded<T>> for String
+where
+    T: Deserialize<'de>,
+{
+    fn decode_borrowed<F: TryInto<ContentType, Error = impl Into<crate::Error>>>(
+        &'de self,
+        content_type: F,
+    ) -> Result<Decoded<T>> {
+        self.as_bytes().decode_borrowed(content_type)
+    }
+}
+
+/// Encoded data, along with the `ContentType` and `EncodeOptions` it was encoded with when it
+/// comes from an encoder.
+#[derive(Clone, Debug)]
 pub struct Encoded {
     inner: Vec<u8>,
+    content_type: Option<ContentType>,
+    options: Option<EncodeOptions>,
+}
+
+impl Encoded {
+    /// The format the data is encoded in, `None` when the `Encoded` was created from raw data.
+    pub fn content_type(&self) -> Option<&ContentType> {
+        self.content_type.as_ref()
+    }
+
+    /// The options the data was encoded with, `None` when the `Encoded` was created from raw
+    /// data.
+    pub fn options(&self) -> Option<&EncodeOptions> {
+        self.options.as_ref()
+    }
+
+    /// Decode the data with the format it was encoded in, fails with
+    /// `Error::ContentTypeNotDetected` when the format is not known. Use `.decode_auto`, or
+    /// `.decode` through `Deref`, e.g. `encoded.as_slice().decode("json")`, for raw data.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<Decoded<T>> {
+        let content_type = self
+            .content_type
+            .as_ref()
+            .ok_or(Error::ContentTypeNotDetected)?;
+        self.inner.decode(content_type)
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.inner
+    }
 }
 
 impl PartialEq<Self> for Encoded {
@@ -727,23 +1574,23 @@ impl Eq for Encoded {}
 
 impl From<Vec<u8>> for Encoded {
     fn from(v: Vec<u8>) -> Self {
-        Encoded { inner: v }
+        Encoded {
+            inner: v,
+            content_type: None,
+            options: None,
+        }
     }
 }
 
 impl From<String> for Encoded {
     fn from(s: String) -> Self {
-        Encoded {
-            inner: s.as_bytes().to_vec(),
-        }
+        Encoded::from(s.into_bytes())
     }
 }
 
 impl From<&str> for Encoded {
     fn from(s: &str) -> Self {
-        Encoded {
-            inner: s.as_bytes().to_vec(),
-        }
+        Encoded::from(s.as_bytes().to_vec())
     }
 }
 
@@ -798,16 +1645,12 @@ impl TryToString for Encoded {
     }
 }
 
-pub struct Decoded<T>
-where
-    T: DeserializeOwned,
-{
+pub struct Decoded<T> {
     pub(crate) inner: T,
 }
 
 impl<T, E> TryFrom<std::result::Result<T, E>> for Decoded<T>
 where
-    T: DeserializeOwned,
     E: Into<Error>,
 {
     type Error = Error;
@@ -817,13 +1660,13 @@ where
     }
 }
 
-impl<T: DeserializeOwned> From<T> for Decoded<T> {
+impl<T> From<T> for Decoded<T> {
     fn from(t: T) -> Self {
         Decoded { inner: t }
     }
 }
 
-impl<T: DeserializeOwned> Deref for Decoded<T> {
+impl<T> Deref for Decoded<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -831,13 +1674,13 @@ impl<T: DeserializeOwned> Deref for Decoded<T> {
     }
 }
 
-impl<T: DeserializeOwned> DerefMut for Decoded<T> {
+impl<T> DerefMut for Decoded<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.inner
     }
 }
 
-impl<T: DeserializeOwned> Decoded<T> {
+impl<T> Decoded<T> {
     pub fn into(self) -> T {
         self.inner
     }
@@ -845,11 +1688,36 @@ impl<T: DeserializeOwned> Decoded<T> {
 
 #[cfg(test)]
 mod test {
+    #[cfg(feature = "actix-web")]
+    mod test_actix;
+    #[cfg(feature = "tokio")]
+    mod test_async_io;
+    #[cfg(feature = "axum")]
+    mod test_axum;
+    mod test_borrowed;
+    mod test_codec;
     mod test_constants;
+    mod test_detect;
+    mod test_file;
+    #[cfg(feature = "tokio-util")]
+    mod test_framed;
+    #[cfg(feature = "http")]
+    mod test_http;
+    mod test_io;
+    mod test_kind;
+    mod test_limits;
+    mod test_media_type;
+    mod test_negotiate;
+    mod test_options;
+    mod test_path;
+    mod test_stream;
     mod test_trait_impl;
+    mod test_transcode;
 
     use super::serde::{Deserialize, Serialize};
-    use crate::{ContentType, Decoded, Encoded, Error, SimpleDecoder, SimpleEncoder, TryToString};
+    use crate::{
+        avro, ContentType, Decoded, Encoded, Error, SimpleDecoder, SimpleEncoder, TryToString,
+    };
     use std::ops::Deref;
     use test_constants::*;
 
@@ -892,6 +1760,17 @@ mod test {
         }
     }
 
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Foo {
+        bar: String,
+    }
+
+    fn foo(bar: &str) -> Foo {
+        Foo {
+            bar: bar.to_string(),
+        }
+    }
+
     fn deserialize_test(ser_type: &str, compare_object: &[u8]) {
         for i in ["", "application/", "application/x-"] {
             let content_type = format!("{}{}", i, ser_type);
@@ -932,8 +1811,62 @@ mod test {
         assert_eq!(ContentType::Bson, (&"Bson".to_string()).try_into().unwrap());
     }
 
+    #[test]
+    fn test_mime_aliases() {
+        let mut names = std::collections::HashSet::new();
+        for content_type in crate::BUILTIN {
+            assert!(!content_type.aliases().contains(&content_type.mime()));
+            for name in content_type.aliases().iter().chain([&content_type.mime()]) {
+                assert!(names.insert(*name), "{} is used twice", name);
+                assert_eq!(*content_type, ContentType::try_from(*name).unwrap());
+                let upper = name.to_uppercase();
+                assert_eq!(*content_type, ContentType::try_from(upper).unwrap());
+            }
+        }
+        for (name, content_type) in [
+            ("application/vnd.msgpack", ContentType::MessagePack),
+            ("application/msgpack", ContentType::MessagePack),
+            ("text/yaml", ContentType::Yaml),
+            ("application/x-yaml", ContentType::Yaml),
+            ("text/x-toml", ContentType::Toml),
+            ("application/x-www-form-urlencoded", ContentType::Url),
+            ("application/cbor-seq", ContentType::Cbor),
+            ("application/python-pickle", ContentType::Pickle),
+            ("text/json", ContentType::Json),
+        ] {
+            assert_eq!(content_type, ContentType::try_from(name).unwrap());
+        }
+        assert_eq!("application/cbor", ContentType::Cbor.mime());
+        assert_eq!("application/vnd.msgpack", ContentType::MessagePack.mime());
+        assert_eq!("application/x-www-form-urlencoded", ContentType::Url.mime());
+    }
+
+    #[test]
+    fn test_encoded_metadata() {
+        let options = crate::EncodeOptions::new().pretty(true);
+        let encoded = MyStruct::default().encode_with("yaml", &options).unwrap();
+        assert_eq!(Some(&ContentType::Yaml), encoded.content_type());
+        assert_eq!(Some(&options), encoded.options());
+        let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+        assert_eq!(decoded.into(), MyStruct::default());
+
+        let encoded = Encoded::from(EXAMPLE_YAML_DESERIALIZE);
+        assert_eq!(None, encoded.content_type());
+        assert_eq!(None, encoded.options());
+        let decoded: crate::Result<Decoded<MyStruct>> = encoded.decode();
+        assert_eq!(
+            Error::ContentTypeNotDetected,
+            decoded.map(|_| ()).unwrap_err()
+        );
+        assert_eq!(EXAMPLE_YAML_DESERIALIZE.as_bytes(), encoded.into_inner());
+    }
+
     #[test]
     fn test_from_ref_self() {
+        assert_eq!(
+            ContentType::Avro,
+            ContentType::try_from(&ContentType::Avro).unwrap()
+        );
         assert_eq!(
             ContentType::Bson,
             ContentType::try_from(&ContentType::Bson).unwrap()
@@ -1098,6 +2031,64 @@ mod test {
         deserialize_test("url", URL_SERIALIZE.as_bytes());
     }
 
+    #[test]
+    fn test_avro() {
+        let schema = avro::Schema::parse_str(AVRO_SCHEMA).unwrap();
+        avro::register_schema::<MyStruct>(schema);
+        for i in ["", "application/", "application/x-"] {
+            let content_type = format!("{}avro", i);
+            let encoded = MyStruct::default().encode(&content_type).unwrap();
+            let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+            assert_eq!(decoded.into(), MyStruct::default());
+        }
+    }
+
+    #[test]
+    fn test_avro_codecs() {
+        let schema = avro::Schema::parse_str(AVRO_SCHEMA).unwrap();
+        let records = [MyStruct::default(), MyStruct::default()];
+        for codec in [avro::Codec::Null, avro::Codec::Deflate, avro::Codec::Snappy] {
+            let container = avro::to_container_iter(&records, &schema, codec).unwrap();
+            let decoded: Vec<MyStruct> = avro::from_container(&container, Some(&schema)).unwrap();
+            assert_eq!(decoded, vec![MyStruct::default(), MyStruct::default()]);
+            assert!(matches!(
+                container
+                    .decode("avro")
+                    .map(|d: Decoded<MyStruct>| d.into()),
+                Err(Error::DecodeFailure(e))
+                    if matches!(e.error(), Error::AvroUnexpectedRecordCount(2))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_avro_datum() {
+        let schema = avro::Schema::parse_str(AVRO_SCHEMA).unwrap();
+        let datum = avro::to_datum(&MyStruct::default(), &schema).unwrap();
+        let decoded: MyStruct = avro::from_datum(&datum, &schema, None).unwrap();
+        assert_eq!(decoded, MyStruct::default());
+    }
+
+    #[test]
+    fn test_avro_unregistered_schema() {
+        #[derive(Serialize)]
+        struct Unregistered {
+            bar: String,
+        }
+        let err = Unregistered {
+            bar: "foobar".to_string(),
+        }
+        .encode("avro")
+        .map(|_| ())
+        .unwrap_err();
+        match err {
+            Error::EncodeFailure(e) => {
+                assert!(matches!(e.error(), Error::AvroSchemaNotRegistered(_)))
+            }
+            other => panic!("expected an encode failure, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_error_from_bson_error() {
         let err = Error::from(bson::ser::Error::UnsignedIntegerExceededRange(0));
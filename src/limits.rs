@@ -0,0 +1,100 @@
+//! # Limits
+//! Safety limits for decoding untrusted input, set through `DecodeOptions::limits`.
+//!
+//! Apart from the input size, which is checked before anything is decoded, the limits are enforced
+//! by a `Deserializer` wrapping the deserializer of the backend, which makes them work the same way
+//! for every `ContentType`, including formats registered through `codec`. A payload declaring a
+//! collection longer than the limit is rejected before the collection is decoded, when the backend
+//! exposes the declared length.
+//!
+//! Exceeding a limit fails with `Error::LimitExceeded`, regardless of how the backend reports
+//! errors.
+//!
+//! ```rust
+//! use simple_serde::{Decoded, DecodeOptions, Error, LimitKind, Limits, SimpleDecoder};
+//!
+//! let options = DecodeOptions::new().limits(Limits::new().max_collection_length(2));
+//! let decoded: simple_serde::Result<Decoded<Vec<u8>>> = "[1, 2, 3]".decode_with("json", &options);
+//! assert!(matches!(
+//!     decoded,
+//!     Err(Error::LimitExceeded { kind: LimitKind::CollectionLength, limit: 2 })
+//! ));
+//! ```
+
+use crate::{Error, Result};
+use derive_more::Display;
+
+/// The limit that was exceeded, see `Error::LimitExceeded`.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum LimitKind {
+    #[display(fmt = "input bytes")]
+    InputBytes,
+    #[display(fmt = "nesting depth")]
+    Depth,
+    #[display(fmt = "collection length")]
+    CollectionLength,
+    #[display(fmt = "string length")]
+    StringLength,
+    #[display(fmt = "total allocation")]
+    TotalAllocation,
+    /// The size of a frame, see `framed`.
+    #[display(fmt = "frame bytes")]
+    FrameBytes,
+}
+
+/// Limits enforced while decoding, every limit is disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub(crate) input_bytes: Option<usize>,
+    pub(crate) depth: Option<usize>,
+    pub(crate) collection_length: Option<usize>,
+    pub(crate) string_length: Option<usize>,
+    pub(crate) total_allocation: Option<usize>,
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum size of the input in bytes.
+    pub fn max_input_bytes(mut self, bytes: usize) -> Self {
+        self.input_bytes = Some(bytes);
+        self
+    }
+
+    /// Maximum nesting of sequences, maps and enums.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Maximum number of items in a sequence, or entries in a map.
+    pub fn max_collection_length(mut self, length: usize) -> Self {
+        self.collection_length = Some(length);
+        self
+    }
+
+    /// Maximum length in bytes of a single string or byte array.
+    pub fn max_string_length(mut self, length: usize) -> Self {
+        self.string_length = Some(length);
+        self
+    }
+
+    /// Maximum length in bytes of every string and byte array combined.
+    pub fn max_total_allocation(mut self, bytes: usize) -> Self {
+        self.total_allocation = Some(bytes);
+        self
+    }
+
+    /// Check the size of the input, the other limits are enforced while decoding.
+    pub(crate) fn check_input(&self, length: usize) -> Result<()> {
+        match self.input_bytes {
+            Some(limit) if length > limit => Err(Error::LimitExceeded {
+                kind: LimitKind::InputBytes,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! # Media Type
+//! Parsing `Content-Type` header values, like `application/json; charset=utf-8`, into their type,
+//! subtype, structured syntax suffix and parameters.
+//!
+//! A media type is resolved to a `ContentType` by its type and subtype first, using the names
+//! `ContentType::try_from` accepts, then by the `+suffix` of the subtype, so vendor types using a
+//! structured syntax are decoded with the format of their suffix.
+//!
+//! | Media type                               | Content type  |
+//! |------------------------------------------|---------------|
+//! | `application/json; charset=utf-8`        | Json          |
+//! | `application/vnd.acme+json; version=2`   | Json          |
+//! | `application/problem+cbor`               | Cbor          |
+//! | `application/ld+yaml`                    | Yaml          |
+//! | `text/html`                              | `Err`         |
+//!
+//! Suffixes are looked up like any other name, so a codec registered under an alias is also
+//! used for the suffix of that name.
+//!
+//! Type, subtype and parameter names are case insensitive and kept in lowercase, parameter values
+//! are kept as is, with the quotes of a quoted string removed. The parameters are handed to the
+//! decoder with `DecodeOptions::media_type`, built-in text formats only read UTF-8 and fail with
+//! `Error::UnsupportedCharset` for any other `charset`, custom codecs receive the media type in
+//! `Codec::decode_media_type`.
+//!
+//! ```rust
+//! use simple_serde::media_type::MediaType;
+//! use simple_serde::ContentType;
+//!
+//! let media_type = MediaType::parse("application/vnd.acme+json; charset=UTF-8").unwrap();
+//! assert_eq!("application/vnd.acme+json", media_type.essence());
+//! assert_eq!(Some("json"), media_type.suffix());
+//! assert_eq!(Some("UTF-8"), media_type.charset());
+//! assert_eq!(ContentType::Json, media_type.content_type().unwrap());
+//! ```
+
+use crate::{ContentType, Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A media type with its parameters, see the module documentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaType {
+    kind: String,
+    subtype: String,
+    parameters: Vec<(String, String)>,
+}
+
+impl MediaType {
+    /// Parse a media type as found in a `Content-Type` header, failing with
+    /// `Error::InvalidMediaType` when it is malformed.
+    pub fn parse(s: &str) -> Result<MediaType> {
+        let invalid = || Error::InvalidMediaType(s.to_string());
+        let (essence, mut rest) = s.split_once(';').unwrap_or((s, ""));
+        let (kind, subtype) = essence.trim().split_once('/').ok_or_else(invalid)?;
+        if !is_token(kind) || !is_token(subtype) {
+            return Err(invalid());
+        }
+        let mut parameters = Vec::new();
+        loop {
+            rest = rest.trim_start_matches(|c: char| c == ';' || c.is_ascii_whitespace());
+            if rest.is_empty() {
+                break;
+            }
+            let (name, value) = rest.split_once('=').ok_or_else(invalid)?;
+            let (name, value) = (name.trim_end(), value.trim_start());
+            let (value, remainder) = match value.strip_prefix('"') {
+                Some(quoted) => {
+                    let (value, remainder) = unquote(quoted).ok_or_else(invalid)?;
+                    let remainder = remainder.trim_start();
+                    if !remainder.is_empty() && !remainder.starts_with(';') {
+                        return Err(invalid());
+                    }
+                    (value, remainder)
+                }
+                None => {
+                    let (value, remainder) = value.split_once(';').unwrap_or((value, ""));
+                    let value = value.trim_end();
+                    if !is_token(value) {
+                        return Err(invalid());
+                    }
+                    (value.to_string(), remainder)
+                }
+            };
+            if !is_token(name) {
+                return Err(invalid());
+            }
+            parameters.push((name.to_ascii_lowercase(), value));
+            rest = remainder;
+        }
+        Ok(MediaType {
+            kind: kind.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            parameters,
+        })
+    }
+
+    /// The top level type, like `application`.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// The subtype including any suffix, like `vnd.acme+json`.
+    pub fn subtype(&self) -> &str {
+        &self.subtype
+    }
+
+    /// The structured syntax suffix of the subtype, like `json` for `vnd.acme+json`.
+    pub fn suffix(&self) -> Option<&str> {
+        self.subtype
+            .rsplit_once('+')
+            .map(|(_, suffix)| suffix)
+            .filter(|suffix| !suffix.is_empty())
+    }
+
+    /// The type and subtype without parameters, like `application/vnd.acme+json`.
+    pub fn essence(&self) -> String {
+        format!("{}/{}", self.kind, self.subtype)
+    }
+
+    /// The value of the parameter `name`, the first one when it is given more than once.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(parameter, _)| parameter.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The parameters in the order they were given.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.parameters
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// The `charset` parameter.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// Whether the `charset` is missing or one that is read as UTF-8.
+    pub(crate) fn is_utf8(&self) -> bool {
+        self.charset().is_none_or(is_utf8)
+    }
+
+    /// The format of the media type, resolved by its essence and then its suffix.
+    pub fn content_type(&self) -> Result<ContentType> {
+        let essence = self.essence();
+        ContentType::named(&essence).or_else(|e| match self.suffix() {
+            Some(suffix) => ContentType::named(suffix).map_err(|_| e),
+            None => Err(e),
+        })
+    }
+}
+
+/// Whether `charset` is read as UTF-8.
+pub(crate) fn is_utf8(charset: &str) -> bool {
+    ["utf-8", "utf8", "us-ascii"]
+        .iter()
+        .any(|utf8| charset.eq_ignore_ascii_case(utf8))
+}
+
+impl FromStr for MediaType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<MediaType> {
+        MediaType::parse(s)
+    }
+}
+
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.kind, self.subtype)?;
+        for (name, value) in &self.parameters {
+            if is_token(value) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "; {}=\"{}\"", name, escaped)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&MediaType> for ContentType {
+    type Error = Error;
+
+    fn try_from(media_type: &MediaType) -> Result<ContentType> {
+        media_type.content_type()
+    }
+}
+
+impl TryFrom<MediaType> for ContentType {
+    type Error = Error;
+
+    fn try_from(media_type: MediaType) -> Result<ContentType> {
+        media_type.content_type()
+    }
+}
+
+/// A token of RFC 9110 section 5.6.2.
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Read a quoted string up to its closing quote, returning the unescaped value and what follows.
+fn unquote(s: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &s[i + 1..])),
+            '\\' => value.push(chars.next()?.1),
+            c => value.push(c),
+        }
+    }
+    None
+}
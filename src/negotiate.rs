@@ -0,0 +1,198 @@
+//! # Negotiate
+//! Picking the content type of a response from an `Accept` header, following the precedence rules
+//! of RFC 9110 section 12.5.1.
+//!
+//! Every supported content type gets the weight of the most specific media range matching it,
+//! where a range with parameters is more specific than one without, and `type/*` and `*/*` are the
+//! least specific. The content type with the highest weight wins, ties going to the content type
+//! listed first in `supported`. A weight of `q=0` means the content type is not acceptable.
+//!
+//! | Accept                                     | Supported       | Negotiated   |
+//! |--------------------------------------------|-----------------|--------------|
+//! | `application/json;q=0.5, application/cbor` | Json, Cbor      | Cbor         |
+//! | `*/*`                                      | Yaml, Json      | Yaml         |
+//! | `application/*;q=0.2, application/yaml`    | Json, Yaml      | Yaml         |
+//! | `*/*, application/json;q=0`                | Json, Cbor      | Cbor         |
+//! | `text/*`                                   | Cbor, Yaml      | Yaml         |
+//! | `application/json;v=2, application/*;q=0.5`| Json, Cbor      | Json         |
+//! | `text/html`                                | Json            | `None`       |
+//!
+//! Media ranges are matched by the names `ContentType::try_from` accepts, so
+//! `application/x-yaml` and `text/*` match `ContentType::Yaml` through `text/yaml`. A range with
+//! parameters other than `q` only matches when the parameters do, and as none of the formats have
+//! parameters of their own the only one matching is a UTF-8 `charset`. `application/json;v=2`
+//! matches nothing, leaving JSON to the less specific `application/*`.
+//!
+//! ```rust
+//! use simple_serde::ContentType;
+//!
+//! let supported = [ContentType::Json, ContentType::Cbor];
+//! assert_eq!(
+//!     Some(ContentType::Cbor),
+//!     ContentType::negotiate("application/json;q=0.5, application/cbor", &supported)
+//! );
+//! ```
+
+use crate::media_type;
+use crate::ContentType;
+#[cfg(any(feature = "actix-web", feature = "axum"))]
+use crate::{codec, Encoded, Error, Result, SimpleEncoder, BUILTIN};
+#[cfg(any(feature = "actix-web", feature = "axum"))]
+use serde::Serialize;
+
+/// A media range of an `Accept` header.
+struct Range {
+    kind: String,
+    subtype: String,
+    /// Resolved when the range is not a wildcard and names a known content type.
+    content_type: Option<ContentType>,
+    parameters: usize,
+    /// Whether every parameter other than `q` is one the formats have.
+    matchable: bool,
+    /// In thousandths, like the three decimals of the header.
+    weight: u16,
+}
+
+impl Range {
+    fn parse(range: &str) -> Option<Range> {
+        let mut parts = range.split(';').map(str::trim);
+        let (kind, subtype) = parts.next()?.split_once('/')?;
+        if kind.is_empty() || subtype.is_empty() || (kind == "*" && subtype != "*") {
+            return None;
+        }
+        let mut parameters = 0;
+        let mut matchable = true;
+        let mut weight = 1000;
+        for parameter in parts.filter(|parameter| !parameter.is_empty()) {
+            match parameter.split_once('=') {
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("q") => {
+                    weight = parse_weight(value.trim())?;
+                }
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("charset") => {
+                    parameters += 1;
+                    matchable &= media_type::is_utf8(value.trim().trim_matches('"'));
+                }
+                _ => {
+                    parameters += 1;
+                    matchable = false;
+                }
+            }
+        }
+        let content_type = match subtype {
+            "*" => None,
+            _ => ContentType::try_from(format!("{}/{}", kind, subtype)).ok(),
+        };
+        Some(Range {
+            kind: kind.to_lowercase(),
+            subtype: subtype.to_lowercase(),
+            content_type,
+            parameters,
+            matchable,
+            weight,
+        })
+    }
+
+    /// How specific the range is, `None` when it does not match `content_type`.
+    fn matches(&self, content_type: &ContentType) -> Option<(u8, usize)> {
+        if !self.matchable {
+            return None;
+        }
+        match (self.kind.as_str(), self.subtype.as_str()) {
+            ("*", _) => Some((0, self.parameters)),
+            (kind, "*") => {
+                let mut names = std::iter::once(content_type.mime())
+                    .chain(content_type.aliases().iter().copied());
+                names
+                    .any(|name| {
+                        let name_kind = name.split_once('/').map(|(name_kind, _)| name_kind);
+                        name_kind.is_some_and(|name_kind| name_kind.eq_ignore_ascii_case(kind))
+                    })
+                    .then_some((1, self.parameters))
+            }
+            _ => (self.content_type.as_ref() == Some(content_type)).then_some((2, self.parameters)),
+        }
+    }
+}
+
+/// Parse a qvalue, `0` to `1` with at most three decimals.
+fn parse_weight(value: &str) -> Option<u16> {
+    let (integer, decimals) = value.split_once('.').unwrap_or((value, ""));
+    if decimals.len() > 3 || !decimals.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let thousandths = format!("{:0<3}", decimals).parse::<u16>().ok()?;
+    match integer {
+        "0" => Some(thousandths),
+        "1" if thousandths == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+impl ContentType {
+    /// Pick the content type of `supported` that `accept` prefers, `None` when none of them are
+    /// acceptable. See the `negotiate` module documentation for the rules.
+    pub fn negotiate(accept: &str, supported: &[ContentType]) -> Option<ContentType> {
+        acceptable(accept, supported).into_iter().next()
+    }
+
+    /// The content types to respond with for the `Accept` header `accept`, the preferred first,
+    /// out of the built-in formats and any codec named in the header. Every format is acceptable
+    /// when the header is missing, JSON first.
+    #[cfg(any(feature = "actix-web", feature = "axum"))]
+    pub(crate) fn accepted(accept: Option<&str>) -> Vec<ContentType> {
+        let accept = accept.unwrap_or("*/*");
+        let mut supported = BUILTIN.to_vec();
+        supported.extend(accept.split(',').filter_map(|range| {
+            let essence = range.split(';').next().unwrap_or_default().trim();
+            codec::registered(essence)
+        }));
+        acceptable(accept, &supported)
+    }
+}
+
+/// The content types of `supported` that are acceptable, the highest weight first and ties in the
+/// order of `supported`.
+fn acceptable(accept: &str, supported: &[ContentType]) -> Vec<ContentType> {
+    if accept.trim().is_empty() {
+        return supported.to_vec();
+    }
+    let ranges: Vec<Range> = accept.split(',').filter_map(Range::parse).collect();
+    let mut acceptable: Vec<(&ContentType, u16)> = supported
+        .iter()
+        .map(|content_type| {
+            let weight = ranges
+                .iter()
+                .filter_map(|range| range.matches(content_type).map(|rank| (rank, range.weight)))
+                .max_by_key(|(rank, _)| *rank)
+                .map(|(_, weight)| weight)
+                .unwrap_or(0);
+            (content_type, weight)
+        })
+        .filter(|(_, weight)| *weight > 0)
+        .collect();
+    // stable, so ties keep the order of `supported`
+    acceptable.sort_by_key(|(_, weight)| std::cmp::Reverse(*weight));
+    acceptable
+        .into_iter()
+        .map(|(content_type, _)| content_type.clone())
+        .collect()
+}
+
+/// Encode `value` with the first of `accepted` it can be encoded in. Not every format encodes
+/// every type, e.g. Avro needs a registered schema and Toml a table, so a format failing to
+/// encode is skipped rather than failing the response, `Error::NotAcceptable` when none of them
+/// work.
+#[cfg(any(feature = "actix-web", feature = "axum"))]
+pub(crate) fn encode_accepted<T: Serialize>(
+    value: &T,
+    accept: Option<&str>,
+    accepted: Vec<ContentType>,
+) -> Result<(ContentType, Encoded)> {
+    for content_type in accepted {
+        match value.encode(&content_type) {
+            Ok(encoded) => return Ok((content_type, encoded)),
+            Err(e) => log::debug!("Skipping {:?} for the response: {}", content_type, e),
+        }
+    }
+    Err(Error::NotAcceptable(accept.unwrap_or("*/*").to_string()))
+}
@@ -0,0 +1,257 @@
+//! # Options
+//! Configuration of the backends, for when their defaults are not what is needed.
+//!
+//! `EncodeOptions` is passed to `.encode_with`, options that do not apply to the format being
+//! encoded are ignored, making it possible to use the same options for every format. The
+//! defaults produce the same output as `.encode`.
+//!
+//! | Option                    | Formats          |
+//! |---------------------------|------------------|
+//! | `pretty`                  | Json, Ron, Toml  |
+//! | `indent`                  | Json, Ron, Toml  |
+//! | `pickle_protocol`         | Pickle           |
+//! | `ron_struct_names`        | Ron              |
+//! | `pretty_arrays`           | Toml             |
+//! | `message_pack_struct_map` | MessagePack      |
+//!
+//! Yaml is always written in block style, which puts every array item on a line of its own.
+//!
+//! `DecodeOptions` is passed to `.decode_with`. Unlike the encoder options, decoding fails with
+//! `Error::UnsupportedOption` when an option is set that the format is unable to honor.
+//!
+//! | Option             | Formats              |
+//! |--------------------|----------------------|
+//! | `limits`           | All                  |
+//! | `max_input_size`   | All                  |
+//! | `recursion_limit`  | MessagePack, Url     |
+//! | `pickle_options`   | Pickle               |
+//! | `url_strict`       | Url                  |
+//! | `ron_extensions`   | Ron                  |
+//! | `media_type`       | All                  |
+//!
+//! The `charset` of `media_type` has to be UTF-8 for the text formats, the binary formats ignore
+//! it and custom codecs are handed the whole media type, see `Codec::decode_media_type`.
+//!
+//! ```rust
+//! use serde::Serialize;
+//! use simple_serde::{
+//!     Decoded, DecodeOptions, EncodeOptions, Result, SimpleDecoder, SimpleEncoder, TryToString,
+//! };
+//!
+//! #[derive(Serialize)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let options = EncodeOptions::new().pretty(true).indent(4);
+//! let encoded = Foo { bar: "foobar".to_string() }.encode_with("json", &options).unwrap();
+//! assert_eq!("{\n    \"bar\": \"foobar\"\n}", encoded.try_to_string().unwrap());
+//!
+//! let options = DecodeOptions::new().max_input_size(8);
+//! let decoded: Result<Decoded<String>> = r#""foobar""#.decode_with("json", &options);
+//! assert!(decoded.is_ok());
+//! let decoded: Result<Decoded<String>> = r#""foobarbaz""#.decode_with("json", &options);
+//! assert!(decoded.is_err());
+//! ```
+
+use crate::{ContentType, Error, Limits, MediaType, Result};
+
+/// Options used by `.encode_with`, see the module documentation for the formats each option
+/// applies to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    pub(crate) pretty: bool,
+    pub(crate) indent: Option<usize>,
+    pub(crate) pickle_protocol: Option<u8>,
+    pub(crate) ron_struct_names: bool,
+    pub(crate) pretty_arrays: bool,
+    pub(crate) message_pack_struct_map: bool,
+}
+
+impl EncodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write human readable output spanning multiple lines instead of compact output.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Number of spaces used for indentation when pretty printing, defaults to the default of the
+    /// backend. For Toml it applies with `pretty_arrays` as well.
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = Some(width);
+        self
+    }
+
+    /// Pickle protocol version, either 2 or 3. Defaults to 3.
+    pub fn pickle_protocol(mut self, version: u8) -> Self {
+        self.pickle_protocol = Some(version);
+        self
+    }
+
+    /// Write the name of structs, e.g. `Foo(bar: 1)` instead of `(bar: 1)`.
+    pub fn ron_struct_names(mut self, struct_names: bool) -> Self {
+        self.ron_struct_names = struct_names;
+        self
+    }
+
+    /// Put every array item on a line of its own.
+    pub fn pretty_arrays(mut self, pretty_arrays: bool) -> Self {
+        self.pretty_arrays = pretty_arrays;
+        self
+    }
+
+    /// Write structs as maps keyed by field name instead of arrays of the field values.
+    pub fn message_pack_struct_map(mut self, struct_map: bool) -> Self {
+        self.message_pack_struct_map = struct_map;
+        self
+    }
+
+    pub(crate) fn json_indent(&self) -> Option<Vec<u8>> {
+        match self.pretty {
+            true => Some(vec![b' '; self.indent.unwrap_or(2)]),
+            false => None,
+        }
+    }
+
+    pub(crate) fn ron_config(&self) -> Option<ron::ser::PrettyConfig> {
+        let config = ron::ser::PrettyConfig::new().struct_names(self.ron_struct_names);
+        match (self.pretty, self.indent) {
+            (true, Some(indent)) => Some(config.indentor(" ".repeat(indent))),
+            (true, None) => Some(config),
+            (false, _) if self.ron_struct_names => Some(config.depth_limit(0)),
+            (false, _) => None,
+        }
+    }
+
+    pub(crate) fn pickle_options(&self) -> Result<serde_pickle::SerOptions> {
+        match self.pickle_protocol {
+            None | Some(3) => Ok(serde_pickle::SerOptions::new()),
+            Some(2) => Ok(serde_pickle::SerOptions::new().proto_v2()),
+            Some(version) => Err(Error::UnsupportedOption(
+                ContentType::Pickle,
+                format!("pickle_protocol({})", version),
+            )),
+        }
+    }
+}
+
+/// Options used by `.decode_with`, see the module documentation for the formats each option
+/// applies to.
+#[derive(Clone, Debug, Default)]
+pub struct DecodeOptions {
+    pub(crate) limits: Limits,
+    pub(crate) recursion_limit: Option<usize>,
+    pub(crate) pickle_options: Option<serde_pickle::DeOptions>,
+    pub(crate) url_strict: Option<bool>,
+    pub(crate) ron_extensions: Option<ron::extensions::Extensions>,
+    pub(crate) media_type: Option<MediaType>,
+}
+
+impl DecodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Safety limits for untrusted input, see the `limits` module.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Reject input larger than `bytes` before it is decoded, same as
+    /// `Limits::max_input_bytes`.
+    pub fn max_input_size(mut self, bytes: usize) -> Self {
+        self.limits = self.limits.max_input_bytes(bytes);
+        self
+    }
+
+    /// Maximum nesting depth. For Url this is the maximum depth of keys like `a[b][c]`, anything
+    /// nested deeper is kept as part of the key.
+    pub fn recursion_limit(mut self, depth: usize) -> Self {
+        self.recursion_limit = Some(depth);
+        self
+    }
+
+    /// Options for `serde_pickle`, e.g. `DeOptions::new().replace_unresolved_globals()`.
+    pub fn pickle_options(mut self, options: serde_pickle::DeOptions) -> Self {
+        self.pickle_options = Some(options);
+        self
+    }
+
+    /// Reject percent encoded brackets in keys, defaults to `true`.
+    pub fn url_strict(mut self, strict: bool) -> Self {
+        self.url_strict = Some(strict);
+        self
+    }
+
+    /// RON extensions enabled without the `#![enable(...)]` attribute in the input.
+    pub fn ron_extensions(mut self, extensions: ron::extensions::Extensions) -> Self {
+        self.ron_extensions = Some(extensions);
+        self
+    }
+
+    /// The media type the data was sent as, e.g. from a `Content-Type` header, making its
+    /// parameters available to the decoder.
+    pub fn media_type(mut self, media_type: MediaType) -> Self {
+        self.media_type = Some(media_type);
+        self
+    }
+
+    /// Check that every option set is supported by `content_type`.
+    pub(crate) fn check(&self, content_type: &ContentType) -> Result<()> {
+        let unsupported = |option: &str| {
+            Err(Error::UnsupportedOption(
+                content_type.clone(),
+                option.to_string(),
+            ))
+        };
+        let recursion_limit = matches!(content_type, ContentType::MessagePack | ContentType::Url);
+        if self.recursion_limit.is_some() && !recursion_limit {
+            return unsupported("recursion_limit");
+        }
+        if self.pickle_options.is_some() && *content_type != ContentType::Pickle {
+            return unsupported("pickle_options");
+        }
+        if self.url_strict.is_some() && *content_type != ContentType::Url {
+            return unsupported("url_strict");
+        }
+        if self.ron_extensions.is_some() && *content_type != ContentType::Ron {
+            return unsupported("ron_extensions");
+        }
+        if let Some(media_type) = &self.media_type {
+            if is_text(content_type) && !media_type.is_utf8() {
+                let charset = media_type.charset().unwrap_or_default().to_string();
+                return Err(Error::UnsupportedCharset(content_type.clone(), charset));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn url_config(&self) -> serde_qs::Config {
+        serde_qs::Config::new(
+            self.recursion_limit.unwrap_or(5),
+            self.url_strict.unwrap_or(true),
+        )
+    }
+}
+
+/// Formats read as UTF-8 text.
+fn is_text(content_type: &ContentType) -> bool {
+    match content_type {
+        ContentType::Json
+        | ContentType::Json5
+        | ContentType::JsonLines
+        | ContentType::Lexpr
+        | ContentType::Ron
+        | ContentType::Toml
+        | ContentType::Url
+        | ContentType::Yaml => true,
+        #[cfg(feature = "accept-limited-xml-serialize")]
+        ContentType::Xml => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,158 @@
+//! # Path
+//! Decode errors pinpointing the value that failed.
+//!
+//! Errors from the backend while decoding are returned as `Error::DecodeFailure`, holding a
+//! `DecodeError` with the path of the field that failed, e.g. `.items[3].price`, the position in
+//! the input when the backend reports one, and the name of the type being decoded. The path is
+//! tracked by wrapping the deserializer of the backend, so it is available for every
+//! `ContentType`, including the binary formats where the backend error says nothing about where it
+//! happened. Formats writing structs as sequences, like MessagePack and Postcard, still get the
+//! field names in the path.
+//!
+//! | Position                 | Formats                                  |
+//! |--------------------------|------------------------------------------|
+//! | `Position::LineColumn`   | Json, Json5, Lexpr, Ron, Toml, Yaml      |
+//! | `Position::Offset`       | Cbor, Pickle                             |
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use simple_serde::{Decoded, Error, Position, SimpleDecoder};
+//!
+//! #[derive(Deserialize, Debug)]
+//! struct Item {
+//!     price: u32,
+//! }
+//!
+//! #[derive(Deserialize, Debug)]
+//! struct Order {
+//!     items: Vec<Item>,
+//! }
+//!
+//! let decoded: simple_serde::Result<Decoded<Order>> =
+//!     r#"{"items": [{"price": 1}, {"price": "two"}]}"#.decode("json");
+//! match decoded {
+//!     Err(Error::DecodeFailure(e)) => {
+//!         assert_eq!(".items[1].price", e.path());
+//!         assert_eq!(Some(Position::LineColumn { line: 1, column: 40 }), e.position());
+//!         assert!(e.type_name().ends_with("Order"));
+//!         assert!(matches!(e.error(), Error::JsonError(_)));
+//!     }
+//!     _ => panic!("expected a decode failure"),
+//! }
+//! ```
+
+use crate::{ContentType, Error};
+use derive_more::Display;
+use std::fmt;
+
+/// Where in the input decoding failed.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+pub enum Position {
+    /// Byte offset from the start of the input.
+    #[display(fmt = "offset {}", _0)]
+    Offset(usize),
+    /// One-based line and column.
+    #[display(fmt = "line {} column {}", line, column)]
+    LineColumn { line: usize, column: usize },
+}
+
+/// A backend error together with where it happened, see the module documentation.
+#[derive(Clone, Debug)]
+pub struct DecodeError {
+    content_type: ContentType,
+    path: String,
+    position: Option<Position>,
+    type_name: &'static str,
+    error: Error,
+}
+
+impl DecodeError {
+    pub(crate) fn new(
+        content_type: ContentType,
+        type_name: &'static str,
+        path: Option<String>,
+        error: Error,
+    ) -> Self {
+        Self {
+            content_type,
+            path: path.unwrap_or_else(|| ".".to_string()),
+            position: position(&error),
+            type_name,
+            error,
+        }
+    }
+
+    pub fn content_type(&self) -> &ContentType {
+        &self.content_type
+    }
+
+    /// Path of the value that failed in jq style, like `.items[3].price`, `.[0]` or `.` when it
+    /// failed at the root.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Where in the input it failed. Not part of the `Display` output, as the backends reporting
+    /// a position already include it in their error.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// Name of the type being decoded.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The error of the backend.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed decoding {} at `{}`: {}",
+            self.type_name, self.path, self.error
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+fn position(error: &Error) -> Option<Position> {
+    let line_column = |line, column| Some(Position::LineColumn { line, column });
+    match error {
+        Error::CborFailure(e) if !e.is_data() => Some(Position::Offset(e.offset() as usize)),
+        Error::JsonError(e) if e.line() > 0 => line_column(e.line(), e.column()),
+        Error::Json5Error(json5::Error::Message {
+            location: Some(location),
+            ..
+        }) => line_column(location.line, location.column),
+        Error::LexprError(e) => e
+            .location()
+            .and_then(|location| line_column(location.line(), location.column())),
+        Error::PickleError(e) => match &**e {
+            serde_pickle::Error::Eval(_, offset) => Some(Position::Offset(*offset)),
+            _ => None,
+        },
+        Error::RonDecodeError(e) => line_column(e.position.line, e.position.col),
+        // zero based, unlike the others
+        Error::TomlDeserializationFailure(e) => e
+            .line_col()
+            .and_then(|(line, column)| line_column(line + 1, column + 1)),
+        Error::YamlError(e) => e
+            .location()
+            .and_then(|location| line_column(location.line(), location.column())),
+        _ => None,
+    }
+}
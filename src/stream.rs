@@ -0,0 +1,613 @@
+//! # Stream
+//! Record streams, many values of the same type written one after another, like a log file or the
+//! body of a batch request.
+//!
+//! `encode_stream` writes every value of an iterator as a record, and `decode_stream` returns an
+//! iterator decoding one record at a time from a `std::io::Read`, so the input is never read into
+//! memory as a whole. `encode_all` and `decode_all` do the same in memory, `decode_all` stopping at
+//! the first record that fails.
+//!
+//! | Format        | Framing                                                         |
+//! |---------------|-----------------------------------------------------------------|
+//! | `Bson`        | Concatenated documents, each starting with its length           |
+//! | `Cbor`        | A CBOR sequence (RFC 8742), concatenated data items             |
+//! | `JsonLines`   | A JSON value per line, blank lines are skipped                  |
+//! | `MessagePack` | Concatenated values                                             |
+//! | `Postcard`    | COBS encoded frames, each terminated by a zero byte             |
+//! | `Yaml`        | Documents separated by `---`, empty documents are skipped       |
+//!
+//! Any other format fails with `Error::StreamingNotSupported`. Directives like `%YAML 1.2` and
+//! comments before a `---` are part of the YAML document it starts.
+//!
+//! A record that can not be decoded is returned as `Error::RecordFailure`, holding a `RecordError`
+//! with the index of the record and, for line based formats, the line it starts on. Decoding
+//! continues with the next record, unlike reading from the input failing, which ends the stream.
+//! Bson, Cbor and MessagePack records are found by reading through them, so a record that is not
+//! well-formed, e.g. truncated, fails as well but ends the stream, as there is no telling where the
+//! next one starts.
+//!
+//! `decode_stream_with` decodes every record with `DecodeOptions`. `Limits::max_input_bytes`
+//! applies to each record rather than the whole stream, and is checked while the record is read, so
+//! an unterminated line or a garbage BSON length fails with `Error::LimitExceeded` without being
+//! read into memory. As the rest of the record is not read, this ends the stream.
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::{decode_stream, encode_stream, Error};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let mut output = Vec::new();
+//! let foos = ["foo", "bar"].map(|bar| Foo { bar: bar.to_string() });
+//! encode_stream(&mut output, &foos, "jsonl").unwrap();
+//! assert_eq!(b"{\"bar\":\"foo\"}\n{\"bar\":\"bar\"}\n".to_vec(), output);
+//!
+//! let input = b"{\"bar\": \"foo\"}\n\n{\"bar\": 1}\n{\"bar\": \"baz\"}\n";
+//! let mut foos = decode_stream::<Foo, _, _>(&input[..], "jsonl").unwrap();
+//! assert_eq!(Foo { bar: "foo".to_string() }, foos.next().unwrap().unwrap());
+//! match foos.next().unwrap() {
+//!     Err(Error::RecordFailure(e)) => {
+//!         assert_eq!(1, e.index());
+//!         assert_eq!(Some(3), e.line());
+//!     }
+//!     _ => panic!("expected a record failure"),
+//! }
+//! assert_eq!(Foo { bar: "baz".to_string() }, foos.next().unwrap().unwrap());
+//! assert!(foos.next().is_none());
+//! ```
+//!
+//! Multi-document YAML, like Kubernetes manifests:
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use simple_serde::{decode_all, encode_all, Error, TryToString};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Foo {
+//!     bar: String,
+//! }
+//!
+//! let foos = ["foo", "bar"].map(|bar| Foo { bar: bar.to_string() });
+//! let encoded = encode_all(&foos, "yaml").unwrap();
+//! assert_eq!("bar: foo\n---\nbar: bar\n", encoded.try_to_string().unwrap());
+//! assert_eq!(foos, decode_all::<Foo, _>(&encoded, "yaml").unwrap()[..]);
+//!
+//! let decoded = decode_all::<Foo, _>(b"---\nbar: foo\n---\nbaz: 1\n", "yaml");
+//! match decoded {
+//!     Err(Error::RecordFailure(e)) => {
+//!         assert_eq!(1, e.index());
+//!         assert_eq!(Some(3), e.line());
+//!     }
+//!     _ => panic!("expected a record failure"),
+//! }
+//! ```
+
+use crate::{
+    ContentType, DecodeOptions, Decoded, EncodeOptions, Encoded, Error, LimitKind, Result,
+    SimpleDecoder, SimpleEncoder,
+};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+
+/// Encode every value of `values` as a record of `content_type` into `writer`.
+pub fn encode_stream<W, I, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    writer: &mut W,
+    values: I,
+    content_type: F,
+) -> Result<()>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let content_type = content_type.try_into().map_err(|e| e.into())?;
+    check(&content_type)?;
+    for (index, value) in values.into_iter().enumerate() {
+        if index > 0 {
+            writer.write_all(separator(&content_type))?;
+        }
+        write(writer, &value, &content_type)?;
+    }
+    Ok(())
+}
+
+fn write<W: Write, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+    content_type: &ContentType,
+) -> Result<()> {
+    match content_type {
+        ContentType::Postcard => {
+            let mut frame = cobs::encode_vec(&value.encode(content_type)?);
+            frame.push(0);
+            writer.write_all(&frame).map_err(Error::from)
+        }
+        _ => value.encode_to_writer(writer, content_type),
+    }
+}
+
+/// Encode every value of `values` as a record of `content_type`.
+pub fn encode_all<I, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    values: I,
+    content_type: F,
+) -> Result<Encoded>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let content_type = content_type.try_into().map_err(|e| e.into())?;
+    let mut inner = Vec::new();
+    encode_stream(&mut inner, values, &content_type)?;
+    Ok(Encoded {
+        inner,
+        content_type: Some(content_type),
+        options: Some(EncodeOptions::default()),
+    })
+}
+
+/// An iterator decoding the records of `content_type` read from `reader`.
+pub fn decode_stream<T, R, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    reader: R,
+    content_type: F,
+) -> Result<StreamDecoder<T, R>>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    decode_stream_with(reader, content_type, &DecodeOptions::default())
+}
+
+/// An iterator decoding the records of `content_type` read from `reader` with `options`, the
+/// limits apply to every record, see the module documentation.
+pub fn decode_stream_with<T, R, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    reader: R,
+    content_type: F,
+    options: &DecodeOptions,
+) -> Result<StreamDecoder<T, R>>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    let content_type = content_type.try_into().map_err(|e| e.into())?;
+    check(&content_type)?;
+    let reader = BufReader::new(reader);
+    let source = match content_type {
+        ContentType::Yaml => Source::Documents {
+            reader,
+            line: 0,
+            pending: None,
+        },
+        ContentType::JsonLines => Source::Lines { reader, line: 0 },
+        ContentType::Bson => Source::LengthPrefixed { reader },
+        ContentType::Postcard => Source::Frames { reader },
+        _ => Source::Values { reader },
+    };
+    Ok(StreamDecoder {
+        content_type,
+        options: options.clone(),
+        source,
+        index: 0,
+        done: false,
+        marker: PhantomData,
+    })
+}
+
+/// Decode every record of `content_type` in `data`, failing with the first record that can not be
+/// decoded.
+pub fn decode_all<T, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    data: &[u8],
+    content_type: F,
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    decode_all_with(data, content_type, &DecodeOptions::default())
+}
+
+/// Decode every record of `content_type` in `data` with `options`, failing with the first record
+/// that can not be decoded.
+pub fn decode_all_with<T, F: TryInto<ContentType, Error = impl Into<Error>>>(
+    data: &[u8],
+    content_type: F,
+    options: &DecodeOptions,
+) -> Result<Vec<T>>
+where
+    T: DeserializeOwned,
+{
+    decode_stream_with(data, content_type, options)?.collect()
+}
+
+fn check(content_type: &ContentType) -> Result<()> {
+    match content_type {
+        ContentType::Bson
+        | ContentType::Cbor
+        | ContentType::JsonLines
+        | ContentType::MessagePack
+        | ContentType::Postcard
+        | ContentType::Yaml => Ok(()),
+        unsupported => Err(Error::StreamingNotSupported(unsupported.clone())),
+    }
+}
+
+/// Written between two records.
+fn separator(content_type: &ContentType) -> &'static [u8] {
+    match content_type {
+        ContentType::Yaml => b"---\n",
+        _ => b"",
+    }
+}
+
+/// Decodes the records of a stream one at a time, see `decode_stream`.
+pub struct StreamDecoder<T, R> {
+    content_type: ContentType,
+    options: DecodeOptions,
+    source: Source<R>,
+    index: usize,
+    done: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+enum Source<R> {
+    Lines {
+        reader: BufReader<R>,
+        line: usize,
+    },
+    /// `pending` is the line starting the next document and its line number.
+    Documents {
+        reader: BufReader<R>,
+        line: usize,
+        pending: Option<(Vec<u8>, usize)>,
+    },
+    LengthPrefixed {
+        reader: BufReader<R>,
+    },
+    /// Zero terminated COBS frames.
+    Frames {
+        reader: BufReader<R>,
+    },
+    /// Self delimiting values, found by decoding them into `IgnoredAny`.
+    Values {
+        reader: BufReader<R>,
+    },
+}
+
+/// The data of a record and the line it starts on.
+struct Record {
+    data: Vec<u8>,
+    line: Option<usize>,
+}
+
+impl<T, R> StreamDecoder<T, R> {
+    pub fn content_type(&self) -> &ContentType {
+        &self.content_type
+    }
+}
+
+impl<T: DeserializeOwned, R: Read> Iterator for StreamDecoder<T, R> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.done {
+            return None;
+        }
+        let limit = self.options.limits.input_bytes;
+        let record = match &mut self.source {
+            Source::Lines { reader, line } => next_line(reader, line, limit),
+            Source::Documents {
+                reader,
+                line,
+                pending,
+            } => next_document(reader, line, pending, limit),
+            Source::LengthPrefixed { reader } => next_length_prefixed(reader, limit),
+            Source::Frames { reader } => next_frame(reader, limit),
+            Source::Values { reader } => next_value(reader, &self.content_type, limit),
+        };
+        let index = self.index;
+        let record = match record {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(e @ Error::IoError(_)) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+            Err(e) => {
+                self.done = true;
+                return Some(Err(self.failure(index, None, e)));
+            }
+        };
+        self.index += 1;
+        let decoded: Result<Decoded<T>> = match self.content_type {
+            ContentType::Postcard => unframe(&record.data)
+                .and_then(|data| data.decode_with(&self.content_type, &self.options)),
+            _ => record.data.decode_with(&self.content_type, &self.options),
+        };
+        Some(
+            decoded
+                .map(Decoded::into)
+                .map_err(|error| self.failure(index, record.line, error)),
+        )
+    }
+}
+
+impl<T, R> StreamDecoder<T, R> {
+    fn failure(&self, index: usize, line: Option<usize>, error: Error) -> Error {
+        Error::RecordFailure(Box::new(RecordError {
+            content_type: self.content_type.clone(),
+            index,
+            line,
+            error,
+        }))
+    }
+}
+
+/// Like `BufRead::read_until`, failing once the record holds more than `limit` bytes, `record`
+/// being the bytes of the record read before.
+fn read_until<R: Read>(
+    reader: &mut BufReader<R>,
+    delimiter: u8,
+    data: &mut Vec<u8>,
+    record: usize,
+    limit: Option<usize>,
+) -> Result<usize> {
+    let read = match limit {
+        Some(limit) => {
+            let remaining = limit.saturating_sub(record + data.len()) as u64;
+            reader.take(remaining + 1).read_until(delimiter, data)?
+        }
+        None => reader.read_until(delimiter, data)?,
+    };
+    check_limit(record + data.len(), limit)?;
+    Ok(read)
+}
+
+fn check_limit(length: usize, limit: Option<usize>) -> Result<()> {
+    match limit {
+        Some(limit) if length > limit => Err(Error::LimitExceeded {
+            kind: LimitKind::InputBytes,
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Read the next line that is not blank.
+fn next_line<R: Read>(
+    reader: &mut BufReader<R>,
+    line: &mut usize,
+    limit: Option<usize>,
+) -> Result<Option<Record>> {
+    loop {
+        let mut data = Vec::new();
+        if read_until(reader, b'\n', &mut data, 0, limit)? == 0 {
+            return Ok(None);
+        }
+        *line += 1;
+        if !data.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Some(Record {
+                data,
+                line: Some(*line),
+            }));
+        }
+    }
+}
+
+/// Read the next YAML document that is not empty, documents start with a `---` line and end
+/// before the next one or with a `...` line.
+fn next_document<R: Read>(
+    reader: &mut BufReader<R>,
+    line: &mut usize,
+    pending: &mut Option<(Vec<u8>, usize)>,
+    limit: Option<usize>,
+) -> Result<Option<Record>> {
+    loop {
+        let (mut data, start) = pending.take().unwrap_or_else(|| (Vec::new(), *line + 1));
+        loop {
+            let mut next = Vec::new();
+            if read_until(reader, b'\n', &mut next, data.len(), limit)? == 0 {
+                break;
+            }
+            *line += 1;
+            // directives and comments before the marker belong to the document it starts
+            if is_document_start(&next) && !is_prefix(&data) {
+                *pending = Some((next, *line));
+                break;
+            }
+            let end = is_marker(&next, b"...");
+            data.extend(next);
+            if end {
+                break;
+            }
+        }
+        if data.is_empty() {
+            return Ok(None);
+        }
+        if !is_empty_document(&data) {
+            return Ok(Some(Record {
+                data,
+                line: Some(start),
+            }));
+        }
+    }
+}
+
+fn is_document_start(line: &[u8]) -> bool {
+    is_marker(line, b"---")
+}
+
+fn is_marker(line: &[u8], marker: &[u8]) -> bool {
+    line.starts_with(marker) && line.get(marker.len()).is_none_or(u8::is_ascii_whitespace)
+}
+
+/// Whether `data` has nothing but directives, comments and blank lines, which are kept with the
+/// document following them.
+fn is_prefix(data: &[u8]) -> bool {
+    data.split(|byte| *byte == b'\n').all(|line| {
+        let content = line.trim_ascii();
+        content.is_empty() || content.starts_with(b"#") || line.starts_with(b"%")
+    })
+}
+
+/// Whether `data` has nothing but directives, document markers, comments and blank lines.
+fn is_empty_document(data: &[u8]) -> bool {
+    data.split(|byte| *byte == b'\n').all(|line| {
+        match is_document_start(line) || is_marker(line, b"...") {
+            true => is_prefix(&line[3..]),
+            false => is_prefix(line),
+        }
+    })
+}
+
+/// Read the next BSON document, which starts with its length, including the length itself.
+fn next_length_prefixed<R: Read>(
+    reader: &mut BufReader<R>,
+    limit: Option<usize>,
+) -> Result<Option<Record>> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    let mut data = Vec::new();
+    reader.take(4).read_to_end(&mut data)?;
+    let length = match <[u8; 4]>::try_from(data.as_slice()) {
+        Ok(length) => i32::from_le_bytes(length),
+        Err(_) => return Err(bson::de::Error::EndOfStream.into()),
+    };
+    if length < 5 {
+        let e = format!("document length {} is too short", length);
+        return Err(Error::InvalidFrame(ContentType::Bson, e));
+    }
+    check_limit(length as usize, limit)?;
+    // read rather than allocated up front, the length might be garbage
+    reader.take(length as u64 - 4).read_to_end(&mut data)?;
+    match data.len() < length as usize {
+        // truncated, like the BSON decoder reports it
+        true => Err(bson::de::Error::EndOfStream.into()),
+        false => Ok(Some(Record { data, line: None })),
+    }
+}
+
+/// Read the next frame that is not empty, the zero byte terminating it is left out.
+fn next_frame<R: Read>(reader: &mut BufReader<R>, limit: Option<usize>) -> Result<Option<Record>> {
+    loop {
+        let mut data = Vec::new();
+        if read_until(reader, 0, &mut data, 0, limit)? == 0 {
+            return Ok(None);
+        }
+        if data.last() == Some(&0) {
+            data.pop();
+        }
+        if !data.is_empty() {
+            return Ok(Some(Record { data, line: None }));
+        }
+    }
+}
+
+fn unframe(frame: &[u8]) -> Result<Vec<u8>> {
+    cobs::decode_vec(frame).map_err(|_| {
+        Error::InvalidFrame(ContentType::Postcard, "invalid COBS encoding".to_string())
+    })
+}
+
+/// Read the next value by decoding it into `IgnoredAny`, keeping the bytes read.
+fn next_value<R: Read>(
+    reader: &mut BufReader<R>,
+    content_type: &ContentType,
+    limit: Option<usize>,
+) -> Result<Option<Record>> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    // one byte over the limit tells a value exceeding it from one ending at it
+    let limited = limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+    let mut recording = Recording {
+        reader: &mut reader.take(limited),
+        data: Vec::new(),
+    };
+    let ignored: Result<IgnoredAny> = match content_type {
+        ContentType::Cbor => {
+            IgnoredAny::deserialize(&mut serde_cbor::Deserializer::from_reader(&mut recording))
+                .map_err(Error::from)
+        }
+        _ => IgnoredAny::deserialize(&mut rmp_serde::Deserializer::new(&mut recording))
+            .map_err(Error::from),
+    };
+    // a value cut off by the limit fails as exceeding it, not as truncated
+    check_limit(recording.data.len(), limit)?;
+    ignored?;
+    Ok(Some(Record {
+        data: recording.data,
+        line: None,
+    }))
+}
+
+/// Keeps a copy of everything read from `reader`.
+struct Recording<'a, R> {
+    reader: &'a mut R,
+    data: Vec<u8>,
+}
+
+impl<R: Read> Read for Recording<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.data.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+/// A record of a stream that could not be decoded, see the module documentation.
+#[derive(Clone, Debug)]
+pub struct RecordError {
+    content_type: ContentType,
+    index: usize,
+    line: Option<usize>,
+    error: Error,
+}
+
+impl RecordError {
+    pub fn content_type(&self) -> &ContentType {
+        &self.content_type
+    }
+
+    /// Zero-based index of the record in the stream.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// One-based line the record starts on, for line based formats.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// The error decoding the record, positions in it are relative to the record.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed decoding record {}", self.index)?;
+        if let Some(line) = self.line {
+            write!(f, " on line {}", line)?;
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+impl std::error::Error for RecordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,133 @@
+use super::{foo, Foo};
+use crate::actix::{SimpleBody, SimpleResponse};
+use crate::{DecodeOptions, Limits, SimpleDecoder, SimpleEncoder};
+use actix_web::http::{header, StatusCode};
+use actix_web::test::TestRequest;
+use actix_web::{body, FromRequest, Responder, ResponseError};
+use std::collections::HashMap;
+
+async fn extract(request: TestRequest) -> Result<SimpleBody<Foo>, actix_web::Error> {
+    let (req, mut payload) = request.to_http_parts();
+    SimpleBody::<Foo>::from_request(&req, &mut payload).await
+}
+
+#[tokio::test]
+async fn test_simple_body() {
+    for content_type in [
+        "application/json",
+        "application/x-messagepack",
+        "application/yaml",
+    ] {
+        let request = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .set_payload(foo("foobar").encode(content_type).unwrap().to_vec());
+        assert_eq!(foo("foobar"), extract(request).await.unwrap().into_inner());
+    }
+
+    let request = TestRequest::post()
+        .insert_header((header::CONTENT_TYPE, "application/json; charset=utf-8"))
+        .set_payload(r#"{"bar": "foobar"}"#);
+    assert_eq!(foo("foobar"), *extract(request).await.unwrap());
+
+    let request = TestRequest::post()
+        .insert_header((header::CONTENT_TYPE, "application/problem+cbor"))
+        .set_payload(foo("foobar").encode("cbor").unwrap().to_vec());
+    assert_eq!(foo("foobar"), *extract(request).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_simple_body_failures() {
+    let status = |e: actix_web::Error| e.as_response_error().status_code();
+
+    let request = TestRequest::post().set_payload(r#"{"bar": "foobar"}"#);
+    let e = extract(request).await.unwrap_err();
+    assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, status(e));
+
+    for content_type in ["text/plain", "application/json; charset=latin1"] {
+        let request = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, content_type))
+            .set_payload(r#"{"bar": "foobar"}"#);
+        let e = extract(request).await.unwrap_err();
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, status(e));
+    }
+
+    let request = TestRequest::post()
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(r#"{"bar": 1}"#);
+    let e = extract(request).await.unwrap_err();
+    assert_eq!(StatusCode::BAD_REQUEST, status(e));
+
+    let request = TestRequest::post()
+        .app_data(DecodeOptions::new().limits(Limits::new().max_string_length(3)))
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(r#"{"bar": "foobar"}"#);
+    let e = extract(request).await.unwrap_err();
+    assert_eq!(StatusCode::BAD_REQUEST, status(e));
+}
+
+#[tokio::test]
+async fn test_simple_response() {
+    let respond = |accept: Option<&str>| {
+        let request = match accept {
+            Some(accept) => TestRequest::get().insert_header((header::ACCEPT, accept)),
+            None => TestRequest::get(),
+        };
+        SimpleResponse(foo("foobar")).respond_to(&request.to_http_request())
+    };
+
+    for (accept, content_type) in [
+        (None, "application/json"),
+        (Some("*/*"), "application/json"),
+        (
+            Some("text/html, application/yaml;q=0.9"),
+            "application/yaml",
+        ),
+        (Some("application/x-cbor"), "application/cbor"),
+        // formats failing to encode `Foo` are skipped
+        (
+            Some("application/avro, application/yaml;q=0.5"),
+            "application/yaml",
+        ),
+    ] {
+        let response = respond(accept);
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            content_type,
+            response.headers().get(header::CONTENT_TYPE).unwrap()
+        );
+        let bytes = body::to_bytes(response.into_body()).await.unwrap();
+        let decoded: crate::Decoded<Foo> = bytes.as_ref().decode(content_type).unwrap();
+        assert_eq!(foo("foobar"), decoded.into());
+    }
+
+    for accept in ["text/html", "application/avro"] {
+        let response = respond(Some(accept));
+        assert_eq!(StatusCode::NOT_ACCEPTABLE, response.status(), "{}", accept);
+    }
+}
+
+#[test]
+fn test_error_status() {
+    let e = crate::Error::NotAcceptable("text/html".to_string());
+    assert_eq!(StatusCode::NOT_ACCEPTABLE, e.status_code());
+    let e = crate::Error::ContentTypeNotDetected;
+    assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, e.status_code());
+}
+
+#[tokio::test]
+async fn test_error_response_hides_internal_errors() {
+    let e = HashMap::from([(vec![1u8], 2u32)])
+        .encode("json")
+        .map(|_| ())
+        .unwrap_err();
+    let response = e.error_response();
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    let bytes = body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(&b"Internal Server Error"[..], bytes.as_ref());
+
+    let e = crate::Error::NotAcceptable("text/html".to_string());
+    let bytes = body::to_bytes(e.error_response().into_body())
+        .await
+        .unwrap();
+    assert_eq!(e.to_string().as_bytes(), bytes.as_ref());
+}
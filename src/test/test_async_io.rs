@@ -0,0 +1,82 @@
+use super::MyStruct;
+use crate::async_io::{decode_async, encode_async, AsyncDecoder, AsyncEncoder};
+use crate::{ContentType, Decoded, SimpleEncoder};
+use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+const ASYNC_TYPES: [ContentType; 13] = [
+    ContentType::Bson,
+    ContentType::Cbor,
+    ContentType::FlexBuffers,
+    ContentType::Json,
+    ContentType::Json5,
+    ContentType::Lexpr,
+    ContentType::MessagePack,
+    ContentType::Pickle,
+    ContentType::Postcard,
+    ContentType::Ron,
+    ContentType::Toml,
+    ContentType::Url,
+    ContentType::Yaml,
+];
+
+#[tokio::test]
+async fn test_async_round_trip() {
+    for content_type in ASYNC_TYPES {
+        let (mut client, server) = duplex(32);
+        let (encoded, decoded) = tokio::join!(
+            async {
+                encode_async(&MyStruct::default(), &mut client, &content_type).await?;
+                client.shutdown().await.map_err(crate::Error::from)
+            },
+            decode_async::<MyStruct, _, _>(server, &content_type)
+        );
+        encoded.unwrap();
+        assert_eq!(decoded.unwrap().into(), MyStruct::default());
+    }
+}
+
+#[tokio::test]
+async fn test_async_encoder_cancellation() {
+    let (client, mut server) = duplex(16);
+    let mut encoder = AsyncEncoder::new(client);
+    let my_struct = MyStruct::default();
+    tokio::select! {
+        biased;
+        _ = encoder.encode(&my_struct, "json") => panic!("duplex buffer should be full"),
+        _ = async {} => {}
+    }
+    assert!(!encoder.pending().is_empty());
+
+    let reader = tokio::spawn(async move {
+        let mut data = Vec::new();
+        server.read_to_end(&mut data).await.unwrap();
+        data
+    });
+    encoder.flush().await.unwrap();
+    encoder.get_mut().shutdown().await.unwrap();
+    drop(encoder);
+    assert_eq!(
+        *MyStruct::default().encode("json").unwrap(),
+        reader.await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_async_decoder_cancellation() {
+    let encoded = MyStruct::default().encode("cbor").unwrap();
+    let (mut client, server) = duplex(1024);
+    let mut decoder = AsyncDecoder::new(server);
+
+    client.write_all(&encoded[..10]).await.unwrap();
+    tokio::select! {
+        biased;
+        _ = decoder.decode::<MyStruct, _>("cbor") => panic!("reader has not reached EOF"),
+        _ = async {} => {}
+    }
+    assert_eq!(&encoded[..10], decoder.buffered());
+
+    client.write_all(&encoded[10..]).await.unwrap();
+    client.shutdown().await.unwrap();
+    let decoded: Decoded<MyStruct> = decoder.decode("cbor").await.unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
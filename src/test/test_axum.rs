@@ -0,0 +1,154 @@
+use super::{foo, Foo};
+use crate::axum::{negotiate, Simple};
+use crate::{DecodeOptions, Decoded, Limits, SimpleDecoder, SimpleEncoder};
+use axum::body::Body;
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::http::{Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{middleware, Extension, Router};
+use std::collections::HashMap;
+use tower::ServiceExt;
+
+async fn echo(Simple(foo): Simple<Foo>) -> Simple<Foo> {
+    Simple(foo)
+}
+
+fn app() -> Router {
+    Router::new()
+        .route("/echo", post(echo))
+        .layer(middleware::from_fn(negotiate))
+}
+
+async fn call(app: Router, headers: &[(&str, &str)], body: impl Into<Body>) -> Response {
+    let mut request = Request::post("/echo");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    app.oneshot(request.body(body.into()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn decode_response(response: Response) -> Foo {
+    let content_type = response.headers()[CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let decoded: Decoded<Foo> = body.as_ref().decode(content_type).unwrap();
+    decoded.into()
+}
+
+#[tokio::test]
+async fn test_simple() {
+    for (content_type, accept) in [
+        ("application/json", "application/vnd.msgpack"),
+        ("application/x-messagepack", "application/yaml"),
+        (
+            "application/yaml; charset=utf-8",
+            "text/html, application/cbor",
+        ),
+        ("application/vnd.foo+json", "application/json"),
+    ] {
+        let body = foo("foobar")
+            .encode(content_type.split(';').next().unwrap())
+            .unwrap();
+        let headers = [
+            (CONTENT_TYPE.as_str(), content_type),
+            (ACCEPT.as_str(), accept),
+        ];
+        let response = call(app(), &headers, body.to_vec()).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            accept.rsplit(", ").next().unwrap(),
+            response.headers()[CONTENT_TYPE]
+        );
+        assert_eq!(foo("foobar"), decode_response(response).await);
+    }
+}
+
+#[tokio::test]
+async fn test_simple_defaults_to_json() {
+    let headers = [(CONTENT_TYPE.as_str(), "application/json")];
+    let body = r#"{"bar": "foobar"}"#;
+    let response = call(app(), &headers, body).await;
+    assert_eq!("application/json", response.headers()[CONTENT_TYPE]);
+    assert_eq!(foo("foobar"), decode_response(response).await);
+}
+
+#[tokio::test]
+async fn test_simple_without_negotiate() {
+    // the `Accept` header is unknown outside the middleware, so it is not answered with JSON
+    let app = Router::new().route("/echo", post(echo));
+    let headers = [
+        (CONTENT_TYPE.as_str(), "application/json"),
+        (ACCEPT.as_str(), "application/yaml"),
+    ];
+    let response = call(app, &headers, r#"{"bar": "foobar"}"#).await;
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("`negotiate` middleware"));
+}
+
+#[tokio::test]
+async fn test_simple_failures() {
+    let body = r#"{"bar": "foobar"}"#;
+    let response = call(app(), &[], body).await;
+    assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, response.status());
+
+    for content_type in ["text/plain", "application/json; charset=latin1", "json;"] {
+        let headers = [(CONTENT_TYPE.as_str(), content_type)];
+        let response = call(app(), &headers, body).await;
+        assert_eq!(StatusCode::UNSUPPORTED_MEDIA_TYPE, response.status());
+    }
+
+    // Avro can not encode `Foo` without a registered schema
+    for accept in ["text/html", "application/avro"] {
+        let headers = [
+            (CONTENT_TYPE.as_str(), "application/json"),
+            (ACCEPT.as_str(), accept),
+        ];
+        let response = call(app(), &headers, body).await;
+        assert_eq!(StatusCode::NOT_ACCEPTABLE, response.status(), "{}", accept);
+    }
+
+    let headers = [(CONTENT_TYPE.as_str(), "application/json")];
+    let response = call(app(), &headers, r#"{"bar": 1}"#).await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+    let options = DecodeOptions::new().limits(Limits::new().max_string_length(3));
+    let app = app().layer(Extension(options));
+    let response = call(app, &headers, body).await;
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+}
+
+#[tokio::test]
+async fn test_simple_skips_formats_failing_to_encode() {
+    let headers = [
+        (CONTENT_TYPE.as_str(), "application/json"),
+        (ACCEPT.as_str(), "application/avro, application/yaml;q=0.5"),
+    ];
+    let response = call(app(), &headers, r#"{"bar": "foobar"}"#).await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("application/yaml", response.headers()[CONTENT_TYPE]);
+    assert_eq!(foo("foobar"), decode_response(response).await);
+}
+
+#[tokio::test]
+async fn test_error_response_hides_internal_errors() {
+    let e = HashMap::from([(vec![1u8], 2u32)])
+        .encode("json")
+        .map(|_| ())
+        .unwrap_err();
+    let response = e.into_response();
+    assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(&b"Internal Server Error"[..], body.as_ref());
+}
@@ -0,0 +1,83 @@
+use crate::{ContentType, Decoded, Error, SimpleBorrowedDecoder, SimpleEncoder};
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Borrowed<'a> {
+    name: &'a str,
+    tags: Vec<&'a str>,
+    count: u32,
+}
+
+#[derive(Serialize)]
+struct OwnedBytes {
+    #[serde(serialize_with = "as_bytes")]
+    data: Vec<u8>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct BorrowedBytes<'a> {
+    data: &'a [u8],
+}
+
+fn as_bytes<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bytes(data)
+}
+
+const BORROWING_TYPES: [ContentType; 10] = [
+    ContentType::Bson,
+    ContentType::Cbor,
+    ContentType::FlexBuffers,
+    ContentType::Json,
+    ContentType::MessagePack,
+    ContentType::Postcard,
+    ContentType::Ron,
+    ContentType::Toml,
+    ContentType::Url,
+    ContentType::Yaml,
+];
+
+fn borrowed() -> Borrowed<'static> {
+    Borrowed {
+        name: "foobar",
+        tags: vec!["foo", "bar"],
+        count: 42,
+    }
+}
+
+#[test]
+fn test_decode_borrowed_str() {
+    for content_type in BORROWING_TYPES {
+        let encoded = borrowed().encode(&content_type).unwrap();
+        let decoded: Decoded<Borrowed> = encoded.decode_borrowed(&content_type).unwrap();
+        assert_eq!(decoded.into(), borrowed(), "{:?}", content_type);
+    }
+}
+
+#[test]
+fn test_decode_borrowed_bytes() {
+    let owned = OwnedBytes {
+        data: vec![0, 1, 2, 3, 255],
+    };
+    for content_type in [
+        ContentType::Cbor,
+        ContentType::FlexBuffers,
+        ContentType::MessagePack,
+        ContentType::Postcard,
+    ] {
+        let encoded = owned.encode(&content_type).unwrap();
+        let decoded: Decoded<BorrowedBytes> = encoded.decode_borrowed(&content_type).unwrap();
+        assert_eq!(decoded.data, owned.data.as_slice(), "{:?}", content_type);
+    }
+}
+
+#[test]
+fn test_decode_borrowed_unsupported() {
+    let encoded = borrowed().encode("pickle").unwrap();
+    assert_eq!(
+        Error::BorrowedDecodingNotSupported(ContentType::Pickle),
+        encoded
+            .decode_borrowed("pickle")
+            .map(|_: Decoded<Borrowed>| ())
+            .unwrap_err()
+    );
+}
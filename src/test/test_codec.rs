@@ -0,0 +1,149 @@
+use super::test_constants::*;
+use super::MyStruct;
+use crate::codec::{self, Codec, CodecRegistry, DecodeVisitor};
+use crate::{
+    decode_from_reader, transcode, ContentType, DecodeOptions, Decoded, Error, LimitKind, Limits,
+    SimpleDecoder, SimpleEncoder,
+};
+use std::io::Cursor;
+
+struct PrettyJson(&'static str);
+
+impl Codec for PrettyJson {
+    fn mime(&self) -> &str {
+        self.0
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> crate::Result<Vec<u8>> {
+        serde_json::to_vec_pretty(value).map_err(Error::from)
+    }
+
+    fn decode(&self, data: &[u8], visit: DecodeVisitor) -> crate::Result<()> {
+        let mut de = serde_json::Deserializer::from_slice(data);
+        visit(&mut <dyn erased_serde::Deserializer>::erase(&mut de))
+    }
+}
+
+struct Silent;
+
+impl Codec for Silent {
+    fn mime(&self) -> &str {
+        "application/x-silent"
+    }
+
+    fn encode(&self, _: &dyn erased_serde::Serialize) -> crate::Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    fn decode(&self, _: &[u8], _: DecodeVisitor) -> crate::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_registry_lookup() {
+    let mut registry = CodecRegistry::new();
+    let pretty = registry.register(PrettyJson("application/x-pretty-json"), &["json"]);
+    registry.alias("application/vnd.test+json", ContentType::Json);
+
+    assert_eq!(pretty, registry.lookup("JSON").unwrap());
+    assert_eq!(
+        pretty,
+        registry.lookup("application/x-pretty-json").unwrap()
+    );
+    assert_eq!(
+        ContentType::Json,
+        registry.lookup("application/vnd.test+json").unwrap()
+    );
+    assert_eq!(ContentType::Yaml, registry.lookup("yaml").unwrap());
+    assert_eq!(
+        Error::UnknownContentTypeMatchFromStr("unknown".to_string()),
+        registry.lookup("unknown").unwrap_err()
+    );
+    assert_eq!(ContentType::Json, ContentType::try_from("json").unwrap());
+}
+
+#[test]
+fn test_registry_encode_decode() {
+    let mut registry = CodecRegistry::new();
+    registry.register(PrettyJson("application/x-pretty-json"), &["json"]);
+
+    let encoded = MyStruct::default()
+        .encode(registry.lookup("json").unwrap())
+        .unwrap();
+    let expected = serde_json::to_vec_pretty(&MyStruct::default()).unwrap();
+    assert_eq!(expected, *encoded);
+    let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+    // through the registry, not the content type remembered by `Encoded`
+    let decoded: Decoded<MyStruct> = encoded
+        .as_slice()
+        .decode(registry.lookup("json").unwrap())
+        .unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_global_registry() {
+    let custom = codec::register(PrettyJson("application/x-test-codec"), &["test-codec"]);
+    assert_eq!(custom, ContentType::try_from("Test-Codec").unwrap());
+    assert_eq!(custom, codec::global().lookup("test-codec").unwrap());
+
+    let encoded = MyStruct::default().encode("test-codec").unwrap();
+    let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+    let decoded: Decoded<MyStruct> = encoded
+        .as_slice()
+        .decode("application/x-test-codec")
+        .unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+
+    let mut writer = Vec::new();
+    MyStruct::default()
+        .encode_to_writer(&mut writer, "test-codec")
+        .unwrap();
+    assert_eq!(*encoded, writer);
+    let decoded: Decoded<MyStruct> = decode_from_reader(Cursor::new(writer), "test-codec").unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+
+    let transcoded = transcode(EXAMPLE_YAML_DESERIALIZE.as_bytes(), "yaml", "test-codec").unwrap();
+    assert_eq!(*encoded, *transcoded);
+    let transcoded = transcode(&encoded, "test-codec", "json").unwrap();
+    let decoded: Decoded<MyStruct> = transcoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_codec_errors() {
+    let mut registry = CodecRegistry::new();
+    let pretty = registry.register(PrettyJson("application/x-pretty-json"), &[]);
+    let silent = registry.register(Silent, &[]);
+
+    let decoded: crate::Result<Decoded<MyStruct>> = "{".decode(&pretty);
+    assert!(matches!(
+        decoded,
+        Err(Error::DecodeFailure(e)) if matches!(e.error(), Error::CodecFailure(_))
+    ));
+    let decoded: crate::Result<Decoded<MyStruct>> = "{}".decode(&silent);
+    assert_eq!(
+        "Failed decoding simple_serde::test::MyStruct at `.`: \
+         Custom codec encoder/decoder error: application/x-silent did not decode a value",
+        decoded.map(|_| ()).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_codec_limits() {
+    let mut registry = CodecRegistry::new();
+    let pretty = registry.register(PrettyJson("application/x-pretty-json"), &[]);
+    let encoded = MyStruct::default().encode(&pretty).unwrap();
+    let options = DecodeOptions::new().limits(Limits::new().max_collection_length(1));
+    let decoded: crate::Result<Decoded<MyStruct>> = encoded.decode_with(&pretty, &options);
+    assert_eq!(
+        Error::LimitExceeded {
+            kind: LimitKind::CollectionLength,
+            limit: 1
+        },
+        decoded.map(|_| ()).unwrap_err()
+    );
+}
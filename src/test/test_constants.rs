@@ -58,7 +58,9 @@ andIn:
 backwardsCompatible: with JSON"#;
 
 // ContentType::Xml
+#[cfg(feature = "accept-limited-xml-serialize")]
 pub(crate) const XML_SERIALIZE: &str = r#"<?xml version="1.0" encoding="UTF-8"?><MyStruct><unquoted>and you can quote me on that</unquoted><singleQuotes>I can use "double quotes" here</singleQuotes><lineBreaks>Look, Mom! No \n's!</lineBreaks><hexadecimal>912559</hexadecimal><leadingDecimalPoint>0.8675309</leadingDecimalPoint><andTrailing>8675309</andTrailing><positiveSign>1</positiveSign><trailingComma>in objects</trailingComma><andIn>arrays</andIn><andIn>arrays-2</andIn><backwardsCompatible>with JSON</backwardsCompatible></MyStruct>"#;
+#[cfg(feature = "accept-limited-xml-serialize")]
 pub(crate) const XML_DESERIALIZE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
 <MyStruct>
     <unquoted>and you can quote me on that</unquoted>
@@ -217,3 +219,20 @@ pub(crate) const POSTCARD_SERIALIZE: &[u8] = &[
 ];
 // ContentType::Url
 pub(crate) const URL_SERIALIZE: &str = r#"unquoted=and+you+can+quote+me+on+that&singleQuotes=I+can+use+%22double+quotes%22+here&lineBreaks=Look%2C+Mom%21+No+%5Cn%27s%21&hexadecimal=912559&leadingDecimalPoint=0.8675309&andTrailing=8675309&positiveSign=1&trailingComma=in+objects&andIn[0]=arrays&andIn[1]=arrays-2&backwardsCompatible=with+JSON"#;
+// ContentType::Avro
+pub(crate) const AVRO_SCHEMA: &str = r#"{
+  "type": "record",
+  "name": "MyStruct",
+  "fields": [
+    {"name": "unquoted", "type": "string"},
+    {"name": "singleQuotes", "type": "string"},
+    {"name": "lineBreaks", "type": "string"},
+    {"name": "hexadecimal", "type": "int"},
+    {"name": "leadingDecimalPoint", "type": "double"},
+    {"name": "andTrailing", "type": "double"},
+    {"name": "positiveSign", "type": "long"},
+    {"name": "trailingComma", "type": "string"},
+    {"name": "andIn", "type": {"type": "array", "items": "string"}},
+    {"name": "backwardsCompatible", "type": "string"}
+  ]
+}"#;
@@ -0,0 +1,102 @@
+use super::test_constants::*;
+use super::MyStruct;
+use crate::{avro, Confidence, ContentType, Decoded, Error, SimpleDecoder, SimpleEncoder};
+use serde::Serialize;
+
+#[test]
+fn test_detect_encoded() {
+    for content_type in [
+        ContentType::Bson,
+        ContentType::Cbor,
+        ContentType::FlexBuffers,
+        ContentType::Json,
+        ContentType::Lexpr,
+        ContentType::MessagePack,
+        ContentType::Pickle,
+        ContentType::Ron,
+        ContentType::Toml,
+        ContentType::Url,
+        ContentType::Yaml,
+    ] {
+        let encoded = MyStruct::default().encode(&content_type).unwrap();
+        let (detected, _) = ContentType::detect(&encoded).unwrap();
+        assert_eq!(content_type, detected);
+    }
+}
+
+#[test]
+fn test_detect_magic_bytes() {
+    let schema = avro::Schema::parse_str(AVRO_SCHEMA).unwrap();
+    let container = avro::to_container(&MyStruct::default(), &schema, avro::Codec::Null).unwrap();
+    assert_eq!(
+        Some((ContentType::Avro, Confidence::Certain)),
+        ContentType::detect(&container)
+    );
+
+    let mut cbor = serde_cbor::Serializer::new(Vec::new());
+    cbor.self_describe().unwrap();
+    MyStruct::default().serialize(&mut cbor).unwrap();
+    assert_eq!(
+        Some((ContentType::Cbor, Confidence::Certain)),
+        ContentType::detect(&cbor.into_inner())
+    );
+}
+
+#[test]
+fn test_detect_ranked_candidates() {
+    assert_eq!(
+        vec![
+            (ContentType::Json, Confidence::High),
+            (ContentType::Json5, Confidence::Medium),
+            (ContentType::Yaml, Confidence::Low),
+            (ContentType::Ron, Confidence::Low),
+        ],
+        ContentType::detect_all(EXAMPLE_JSON_DESERIALIZE.as_bytes())
+    );
+    assert_eq!(
+        vec![(ContentType::Json5, Confidence::High)],
+        ContentType::detect_all(EXAMPLE_JSON5_DESERIALIZE.as_bytes())
+    );
+    assert_eq!(
+        vec![(ContentType::Yaml, Confidence::Medium)],
+        ContentType::detect_all(EXAMPLE_YAML_DESERIALIZE.as_bytes())
+    );
+}
+
+#[test]
+fn test_detect_unknown() {
+    let postcard = MyStruct::default().encode("postcard").unwrap();
+    assert_eq!(None, ContentType::detect(&postcard));
+    assert_eq!(None, ContentType::detect(b""));
+    assert_eq!(None, ContentType::detect(b"just some text"));
+}
+
+#[test]
+fn test_decode_auto() {
+    for input in [
+        EXAMPLE_JSON_DESERIALIZE,
+        EXAMPLE_JSON5_DESERIALIZE,
+        EXAMPLE_YAML_DESERIALIZE,
+        TOML_SERIALIZE,
+    ] {
+        let decoded: Decoded<MyStruct> = input.decode_auto().unwrap();
+        assert_eq!(decoded.into(), MyStruct::default());
+    }
+    let pickle = MyStruct::default().encode("pickle").unwrap();
+    let decoded: Decoded<MyStruct> = pickle.to_vec().decode_auto().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_decode_auto_errors() {
+    let decoded: crate::Result<Decoded<MyStruct>> = "just some text".decode_auto();
+    assert_eq!(
+        Error::ContentTypeNotDetected,
+        decoded.map(|_| ()).unwrap_err()
+    );
+    let decoded: crate::Result<Decoded<MyStruct>> = r#"{"foo": "bar"}"#.decode_auto();
+    assert!(matches!(
+        decoded,
+        Err(Error::DecodeFailure(e)) if matches!(e.error(), Error::JsonError(_))
+    ));
+}
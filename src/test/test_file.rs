@@ -0,0 +1,95 @@
+use super::{foo, Foo};
+use crate::{load, save, ContentType, Decoded, Error};
+use std::fs;
+use std::path::PathBuf;
+
+/// An empty directory of its own for every test.
+fn directory(name: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("simple-serde-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn test_from_extension() {
+    for (extension, content_type) in [
+        ("json", ContentType::Json),
+        (".json5", ContentType::Json5),
+        ("YML", ContentType::Yaml),
+        ("yaml", ContentType::Yaml),
+        ("toml", ContentType::Toml),
+        ("ron", ContentType::Ron),
+        ("cbor", ContentType::Cbor),
+        ("msgpack", ContentType::MessagePack),
+        ("bson", ContentType::Bson),
+        ("pkl", ContentType::Pickle),
+        ("sexp", ContentType::Lexpr),
+    ] {
+        assert_eq!(Some(content_type), ContentType::from_extension(extension));
+    }
+    assert_eq!(None, ContentType::from_extension("txt"));
+    assert_eq!(None, ContentType::from_extension(""));
+
+    assert_eq!(
+        Some(ContentType::Yaml),
+        ContentType::from_path("config/app.Yml")
+    );
+    assert_eq!(None, ContentType::from_path("config/yaml"));
+    assert_eq!(None, ContentType::from_path("config/.yaml/app"));
+
+    assert_eq!(Some("yaml"), ContentType::Yaml.extension());
+    assert_eq!(Some("pkl"), ContentType::Pickle.extension());
+    assert_eq!(None, ContentType::Url.extension());
+}
+
+#[test]
+fn test_load_save() {
+    let directory = directory("load-save");
+    for name in ["foo.json", "foo.yml", "foo.toml", "foo.cbor", "foo.msgpack"] {
+        let path = directory.join(name);
+        save(&path, &foo("foobar")).unwrap();
+        let loaded: Decoded<Foo> = load(&path).unwrap();
+        assert_eq!(foo("foobar"), loaded.into());
+    }
+    // replaces the file, without leaving temporary files behind
+    let path = directory.join("foo.json");
+    save(&path, &Foo { bar: "bar".into() }).unwrap();
+    assert_eq!(r#"{"bar":"bar"}"#, fs::read_to_string(&path).unwrap());
+    assert_eq!(5, fs::read_dir(&directory).unwrap().count());
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn test_load_save_failures() {
+    let directory = directory("failures");
+    let path = directory.join("foo.txt");
+    assert_eq!(
+        Err(Error::UnknownExtension(path.display().to_string())),
+        save(&path, &foo("foobar"))
+    );
+    assert!(!path.exists());
+    let loaded: crate::Result<Decoded<Foo>> = load(&path);
+    assert_eq!(
+        Err(Error::UnknownExtension(path.display().to_string())),
+        loaded.map(|_| ())
+    );
+
+    let loaded: crate::Result<Decoded<Foo>> = load(directory.join("missing.json"));
+    assert!(matches!(loaded, Err(Error::IoError(_))));
+
+    // the target is left alone when encoding fails
+    let path = directory.join("foo.toml");
+    save(&path, &foo("foobar")).unwrap();
+    assert!(save(&path, &vec![Some(1), None]).is_err());
+    let loaded: Decoded<Foo> = load(&path).unwrap();
+    assert_eq!(foo("foobar"), loaded.into());
+
+    let path = directory.join("missing").join("foo.json");
+    assert!(matches!(
+        save(&path, &foo("foobar")),
+        Err(Error::IoError(_))
+    ));
+    fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,191 @@
+use super::{foo, Foo};
+use crate::framed::{Framing, SimpleCodec, DEFAULT_MAX_FRAME_SIZE};
+use crate::{ContentType, Error, ErrorKind, LimitKind};
+use bytes::BytesMut;
+use futures_sink::Sink;
+use futures_util::StreamExt;
+use std::future::poll_fn;
+use std::pin::Pin;
+use tokio::io::AsyncWriteExt;
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+fn codec(content_type: &str, framing: Framing) -> SimpleCodec<Foo> {
+    SimpleCodec::new(content_type, framing).unwrap()
+}
+
+fn encode(codec: &mut SimpleCodec<Foo>, foos: &[Foo]) -> BytesMut {
+    let mut buffer = BytesMut::new();
+    for foo in foos {
+        codec.encode(foo.clone(), &mut buffer).unwrap();
+    }
+    buffer
+}
+
+async fn send<S: Sink<Foo, Error = Error> + Unpin>(sink: &mut S, item: Foo) -> crate::Result<()> {
+    poll_fn(|cx| Pin::new(&mut *sink).poll_ready(cx)).await?;
+    Pin::new(&mut *sink).start_send(item)?;
+    poll_fn(|cx| Pin::new(&mut *sink).poll_flush(cx)).await
+}
+
+#[tokio::test]
+async fn test_framed() {
+    let foos = vec![foo("a"), foo(&"b".repeat(300)), foo("c\nd")];
+    for (content_type, framing) in [
+        ("json", Framing::LengthU32),
+        ("postcard", Framing::Varint),
+        ("jsonl", Framing::Newline),
+        ("messagepack", Framing::Cobs),
+    ] {
+        let (client, server) = tokio::io::duplex(64);
+        let sent = foos.clone();
+        let writer = tokio::spawn(async move {
+            let mut framed = FramedWrite::new(client, codec(content_type, framing));
+            for foo in sent {
+                send(&mut framed, foo).await.unwrap();
+            }
+            framed.into_inner().shutdown().await.unwrap();
+        });
+        let framed = FramedRead::new(server, codec(content_type, framing));
+        let received: Vec<crate::Result<Foo>> = framed.collect().await;
+        writer.await.unwrap();
+        let received: Vec<Foo> = received.into_iter().map(Result::unwrap).collect();
+        assert_eq!(foos, received, "{} {:?}", content_type, framing);
+    }
+}
+
+#[test]
+fn test_framing() {
+    let mut varint = codec("json", Framing::Varint);
+    let buffer = encode(&mut varint, &[foo(&"a".repeat(200))]);
+    assert_eq!(&[0xd2, 0x01], &buffer[..2]);
+    assert_eq!(212, buffer.len());
+
+    for content_type in ["json", "jsonl"] {
+        let mut newline = codec(content_type, Framing::Newline);
+        let buffer = encode(&mut newline, &[foo("a"), foo("b")]);
+        assert_eq!(&b"{\"bar\":\"a\"}\n{\"bar\":\"b\"}\n"[..], &buffer[..]);
+    }
+
+    let mut cobs = codec("postcard", Framing::Cobs);
+    let buffer = encode(&mut cobs, &[foo(""), foo("a")]);
+    assert_eq!(&[0x01, 0x01, 0x00, 0x03, 0x01, b'a', 0x00][..], &buffer[..]);
+
+    assert_eq!(
+        Err(Error::UnsupportedOption(
+            ContentType::Yaml,
+            "newline framing".to_string()
+        )),
+        SimpleCodec::<Foo>::new("yaml", Framing::Newline).map(|_| ())
+    );
+}
+
+#[test]
+fn test_partial_frames() {
+    for (content_type, framing) in [
+        ("json", Framing::LengthU32),
+        ("bson", Framing::Varint),
+        ("json", Framing::Newline),
+        ("cbor", Framing::Cobs),
+    ] {
+        let mut codec = codec(content_type, framing);
+        let encoded = encode(&mut codec, &[foo("a"), foo("b")]);
+        let mut buffer = BytesMut::new();
+        let mut decoded = Vec::new();
+        for byte in encoded.iter() {
+            buffer.extend_from_slice(&[*byte]);
+            if let Some(foo) = codec.decode(&mut buffer).unwrap() {
+                decoded.push(foo);
+            }
+        }
+        assert_eq!(vec![foo("a"), foo("b")], decoded, "{:?}", framing);
+        assert!(buffer.is_empty());
+        assert_eq!(None, codec.decode_eof(&mut buffer).unwrap());
+    }
+
+    // the scan continues where it stopped, across chunks holding more than one frame
+    let mut newline = codec("json", Framing::Newline);
+    let mut buffer = BytesMut::new();
+    let mut decoded = Vec::new();
+    for chunk in [
+        "{\"bar\"",
+        ": \"a\"}\n{\"ba",
+        "r\": \"b\"}\n\n{\"bar\": \"c\"}",
+    ] {
+        buffer.extend_from_slice(chunk.as_bytes());
+        while let Some(foo) = newline.decode(&mut buffer).unwrap() {
+            decoded.push(foo);
+        }
+    }
+    decoded.extend(newline.decode_eof(&mut buffer).unwrap());
+    assert_eq!(vec![foo("a"), foo("b"), foo("c")], decoded);
+}
+
+#[test]
+fn test_frame_errors() {
+    // the frame failing is consumed, so the next one decodes
+    let mut newline = codec("json", Framing::Newline);
+    let mut buffer =
+        BytesMut::from(&b"\r\n{\"bar\": 1}\n\n{\"bar\": \"a\"}\r\n{\"bar\": \"b\"}"[..]);
+    let e = newline.decode(&mut buffer).unwrap_err();
+    assert_eq!(ErrorKind::Data, e.kind());
+    assert_eq!(Some(foo("a")), newline.decode(&mut buffer).unwrap());
+    assert_eq!(None, newline.decode(&mut buffer).unwrap());
+    assert_eq!(Some(foo("b")), newline.decode_eof(&mut buffer).unwrap());
+    assert_eq!(None, newline.decode_eof(&mut buffer).unwrap());
+
+    let mut cobs = codec("postcard", Framing::Cobs);
+    let mut buffer = BytesMut::from(&[0x05, 0x01, 0x00][..]);
+    assert_eq!(
+        Error::InvalidFrame(ContentType::Postcard, "invalid COBS encoding".to_string()),
+        cobs.decode(&mut buffer).unwrap_err()
+    );
+
+    let mut length = codec("json", Framing::LengthU32);
+    let mut buffer = BytesMut::from(&b"\0\0\0\x10{\"bar\""[..]);
+    assert_eq!(None, length.decode(&mut buffer).unwrap());
+    assert_eq!(
+        ErrorKind::Eof,
+        length.decode_eof(&mut buffer).unwrap_err().kind()
+    );
+
+    let mut varint = codec("json", Framing::Varint);
+    let mut buffer = BytesMut::from(&[0xff, 0xff, 0xff, 0xff, 0xff][..]);
+    assert_eq!(
+        ErrorKind::LimitExceeded,
+        varint.decode(&mut buffer).unwrap_err().kind()
+    );
+}
+
+#[test]
+fn test_max_frame_size() {
+    let exceeded = |limit| Error::LimitExceeded {
+        kind: LimitKind::FrameBytes,
+        limit,
+    };
+    let length = (DEFAULT_MAX_FRAME_SIZE as u32 + 1).to_be_bytes();
+    let mut buffer = BytesMut::from(&length[..]);
+    assert_eq!(
+        exceeded(DEFAULT_MAX_FRAME_SIZE),
+        codec("json", Framing::LengthU32)
+            .decode(&mut buffer)
+            .unwrap_err()
+    );
+
+    let mut length = codec("json", Framing::LengthU32).max_frame_size(16);
+    assert_eq!(
+        exceeded(16),
+        length
+            .encode(foo("foobar1"), &mut BytesMut::new())
+            .unwrap_err()
+    );
+    assert_eq!(None, exceeded(16).direction());
+    // rejected from the length alone
+    let mut buffer = BytesMut::from(&b"\0\0\0\x11"[..]);
+    assert_eq!(exceeded(16), length.decode(&mut buffer).unwrap_err());
+    let mut buffer = encode(&mut length, &[foo("foobar")]);
+    assert_eq!(Some(foo("foobar")), length.decode(&mut buffer).unwrap());
+
+    let mut newline = codec("json", Framing::Newline).max_frame_size(16);
+    let mut buffer = BytesMut::from(&b"{\"bar\": \"foobar1\""[..]);
+    assert_eq!(exceeded(16), newline.decode(&mut buffer).unwrap_err());
+}
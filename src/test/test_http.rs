@@ -0,0 +1,99 @@
+use super::{foo, Foo};
+use crate::http_io::{decode_request, encode_response};
+use crate::{ContentType, Decoded, Encoded, Error, SimpleEncoder};
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, Request};
+
+#[test]
+fn test_header_value() {
+    for content_type in [
+        ContentType::Json,
+        ContentType::MessagePack,
+        ContentType::Yaml,
+    ] {
+        let header = HeaderValue::try_from(&content_type).unwrap();
+        assert_eq!(content_type.mime(), header);
+        assert_eq!(content_type, ContentType::try_from(&header).unwrap());
+        assert_eq!(content_type, ContentType::try_from(header).unwrap());
+    }
+    let header = HeaderValue::from_static("application/json; charset=utf-8");
+    assert_eq!(ContentType::Json, ContentType::try_from(header).unwrap());
+
+    let header = HeaderValue::from_bytes(b"application/\xffjson").unwrap();
+    assert!(matches!(
+        ContentType::try_from(header),
+        Err(Error::FailedConvertingHeaderValueToContentType(_))
+    ));
+    let header = HeaderValue::from_static("text/html");
+    assert!(matches!(
+        ContentType::try_from(header),
+        Err(Error::UnknownContentTypeMatchFromStr(_))
+    ));
+}
+
+#[cfg(feature = "mime")]
+#[test]
+fn test_mime() {
+    let mime = mime::Mime::try_from(&ContentType::Yaml).unwrap();
+    assert_eq!("application/yaml", mime.essence_str());
+    assert_eq!(ContentType::Yaml, ContentType::try_from(mime).unwrap());
+    assert_eq!(
+        ContentType::Json,
+        ContentType::try_from(mime::APPLICATION_JSON).unwrap()
+    );
+    assert!(ContentType::try_from(mime::TEXT_HTML).is_err());
+}
+
+#[test]
+fn test_encode_response() {
+    let response = encode_response(&foo("foobar"), ContentType::MessagePack).unwrap();
+    assert_eq!("application/vnd.msgpack", response.headers()[CONTENT_TYPE]);
+
+    let request = Request::post("/foo")
+        .header(CONTENT_TYPE, response.headers()[CONTENT_TYPE].clone())
+        .body(response.into_body())
+        .unwrap();
+    let decoded: Decoded<Foo> = decode_request(&request).unwrap();
+    assert_eq!(foo("foobar"), decoded.into());
+}
+
+#[test]
+fn test_into_http_parts() {
+    let (headers, body) = foo("foobar")
+        .encode("yaml")
+        .unwrap()
+        .into_http_parts()
+        .unwrap();
+    assert_eq!("application/yaml", headers[CONTENT_TYPE]);
+    assert_eq!(b"bar: foobar\n".to_vec(), body);
+
+    let parts = Encoded::from(b"bar: foobar\n".to_vec()).into_http_parts();
+    assert!(matches!(parts, Err(Error::ContentTypeNotDetected)));
+}
+
+#[test]
+fn test_decode_request_without_content_type() {
+    let request = Request::post("/foo").body(r#"{"bar": "foobar"}"#).unwrap();
+    let decoded: crate::Result<Decoded<Foo>> = decode_request(&request);
+    assert!(matches!(decoded, Err(Error::ContentTypeNotDetected)));
+}
+
+#[test]
+fn test_decode_request_media_type() {
+    let request = Request::post("/foo")
+        .header(CONTENT_TYPE, "application/problem+json; charset=utf-8")
+        .body(r#"{"bar": "foobar"}"#)
+        .unwrap();
+    let decoded: Decoded<Foo> = decode_request(&request).unwrap();
+    assert_eq!(foo("foobar"), decoded.into());
+
+    let request = Request::post("/foo")
+        .header(CONTENT_TYPE, "application/json; charset=iso-8859-1")
+        .body(r#"{"bar": "foobar"}"#)
+        .unwrap();
+    let decoded: crate::Result<Decoded<Foo>> = decode_request(&request);
+    assert!(matches!(
+        decoded,
+        Err(Error::UnsupportedCharset(ContentType::Json, charset)) if charset == "iso-8859-1"
+    ));
+}
@@ -0,0 +1,53 @@
+use super::test_constants::AVRO_SCHEMA;
+use super::MyStruct;
+use crate::{avro, decode_from_reader, ContentType, Decoded, SimpleEncoder};
+use std::io::Cursor;
+
+const STREAMED_TYPES: [ContentType; 13] = [
+    ContentType::Bson,
+    ContentType::Cbor,
+    ContentType::FlexBuffers,
+    ContentType::Json,
+    ContentType::Json5,
+    ContentType::Lexpr,
+    ContentType::MessagePack,
+    ContentType::Pickle,
+    ContentType::Postcard,
+    ContentType::Ron,
+    ContentType::Toml,
+    ContentType::Url,
+    ContentType::Yaml,
+];
+
+#[test]
+fn test_encode_to_writer_matches_encode() {
+    for content_type in STREAMED_TYPES {
+        let mut writer = Vec::new();
+        MyStruct::default()
+            .encode_to_writer(&mut writer, &content_type)
+            .unwrap();
+        let encoded = MyStruct::default().encode(&content_type).unwrap();
+        assert_eq!(*encoded, writer, "{:?}", content_type);
+    }
+}
+
+#[test]
+fn test_decode_from_reader() {
+    for content_type in STREAMED_TYPES {
+        let encoded = MyStruct::default().encode(&content_type).unwrap();
+        let decoded: Decoded<MyStruct> =
+            decode_from_reader(Cursor::new(encoded.to_vec()), &content_type).unwrap();
+        assert_eq!(decoded.into(), MyStruct::default(), "{:?}", content_type);
+    }
+}
+
+#[test]
+fn test_avro_stream_round_trip() {
+    avro::register_schema::<MyStruct>(avro::Schema::parse_str(AVRO_SCHEMA).unwrap());
+    let mut writer = Vec::new();
+    MyStruct::default()
+        .encode_to_writer(&mut writer, "avro")
+        .unwrap();
+    let decoded: Decoded<MyStruct> = decode_from_reader(writer.as_slice(), "avro").unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
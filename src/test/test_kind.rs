@@ -0,0 +1,140 @@
+use crate::{
+    ContentType, DecodeOptions, Decoded, Direction, Error, ErrorKind, Limits,
+    SimpleBorrowedDecoder, SimpleDecoder, SimpleEncoder,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Item<P> {
+    price: P,
+}
+
+fn decode_error(data: &[u8], content_type: &ContentType) -> Error {
+    let decoded: crate::Result<Decoded<Item<u32>>> = data.decode(content_type);
+    decoded.map(|_| ()).unwrap_err()
+}
+
+#[test]
+fn test_kind_data() {
+    for content_type in [
+        ContentType::Bson,
+        ContentType::Cbor,
+        ContentType::FlexBuffers,
+        ContentType::Json,
+        ContentType::Lexpr,
+        ContentType::MessagePack,
+        ContentType::Pickle,
+        ContentType::Toml,
+        ContentType::Url,
+        ContentType::Yaml,
+    ] {
+        let encoded = Item { price: "one" }.encode(&content_type).unwrap();
+        let e = decode_error(&encoded, &content_type);
+        assert_eq!(ErrorKind::Data, e.kind(), "{:?}: {}", content_type, e);
+        assert_eq!(Some(content_type), e.content_type());
+        assert_eq!(Some(Direction::Decode), e.direction());
+    }
+}
+
+#[test]
+fn test_kind_syntax() {
+    for content_type in [
+        ContentType::Json,
+        ContentType::Json5,
+        ContentType::Ron,
+        ContentType::Toml,
+        ContentType::Yaml,
+    ] {
+        let e = decode_error(b"{price: ]", &content_type);
+        assert_eq!(ErrorKind::Syntax, e.kind(), "{:?}: {}", content_type, e);
+    }
+}
+
+#[test]
+fn test_kind_eof() {
+    for content_type in [
+        ContentType::Cbor,
+        ContentType::MessagePack,
+        ContentType::Pickle,
+        ContentType::Postcard,
+    ] {
+        let encoded = Item { price: u32::MAX }.encode(&content_type).unwrap();
+        let e = decode_error(&encoded[..encoded.len() - 1], &content_type);
+        assert_eq!(ErrorKind::Eof, e.kind(), "{:?}: {}", content_type, e);
+    }
+    let e = decode_error(br#"{"price": 1"#, &ContentType::Json);
+    assert_eq!(ErrorKind::Eof, e.kind());
+}
+
+#[test]
+fn test_kind_not_about_the_input() {
+    let decoded: crate::Result<Decoded<Item<u32>>> = "".decode("application/x-unknown");
+    let e = decoded.map(|_| ()).unwrap_err();
+    assert_eq!(ErrorKind::UnsupportedContentType, e.kind());
+    assert_eq!(None, e.content_type());
+
+    let decoded: crate::Result<Decoded<Item<u32>>> = b"".decode_borrowed(ContentType::Pickle);
+    let e = decoded.map(|_| ()).unwrap_err();
+    assert!(matches!(e, Error::BorrowedDecodingNotSupported(_)));
+    assert_eq!(ErrorKind::Unsupported, e.kind());
+    assert_eq!(Some(ContentType::Pickle), e.content_type());
+    assert_eq!(Some(Direction::Decode), e.direction());
+
+    let options = DecodeOptions::new().limits(Limits::new().max_input_bytes(1));
+    let decoded: crate::Result<Decoded<Item<u32>>> =
+        r#"{"price": 1}"#.decode_with(ContentType::Json, &options);
+    let e = decoded.map(|_| ()).unwrap_err();
+    assert_eq!(ErrorKind::LimitExceeded, e.kind());
+    assert_eq!(Some(Direction::Decode), e.direction());
+}
+
+#[test]
+fn test_kind_borrowed() {
+    let decoded: crate::Result<Decoded<Item<&str>>> =
+        r#"{"price": 1}"#.decode_borrowed(ContentType::Json);
+    let e = decoded.map(|_| ()).unwrap_err();
+    assert!(matches!(e, Error::DecodeFailure(_)));
+    assert_eq!(ErrorKind::Data, e.kind());
+    assert_eq!(Some(ContentType::Json), e.content_type());
+}
+
+#[test]
+fn test_kind_encode() {
+    let e = Item { price: () }
+        .encode(ContentType::Toml)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(ErrorKind::UnsupportedType, e.kind());
+    assert_eq!(Some(ContentType::Toml), e.content_type());
+    assert_eq!(Some(Direction::Encode), e.direction());
+
+    let e = HashMap::from([(1u32, 2u32)])
+        .encode(ContentType::Bson)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(ErrorKind::UnsupportedType, e.kind());
+    assert_eq!(Some(Direction::Encode), e.direction());
+}
+
+#[test]
+fn test_kind_encode_failure() {
+    // the same backend error as decoding, so only the wrapper tells the direction
+    let e = HashMap::from([(vec![1u8], 2u32)])
+        .encode("json")
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(&e, Error::EncodeFailure(e) if matches!(e.error(), Error::JsonError(_))));
+    assert_eq!(ErrorKind::UnsupportedType, e.kind());
+    assert_eq!(Some(ContentType::Json), e.content_type());
+    assert_eq!(Some(Direction::Encode), e.direction());
+
+    for content_type in [ContentType::Json, ContentType::JsonLines] {
+        let mut writer = Vec::new();
+        let e = HashMap::from([(vec![1u8], 2u32)])
+            .encode_to_writer(&mut writer, &content_type)
+            .unwrap_err();
+        assert_eq!(Some(Direction::Encode), e.direction(), "{:?}", content_type);
+        assert_eq!(Some(content_type), e.content_type());
+    }
+}
@@ -0,0 +1,110 @@
+use super::test_constants::*;
+use super::MyStruct;
+use crate::{
+    avro, ContentType, DecodeOptions, Decoded, Error, LimitKind, Limits, SimpleDecoder,
+    SimpleEncoder,
+};
+
+const CONTENT_TYPES: [ContentType; 14] = [
+    ContentType::Avro,
+    ContentType::Bson,
+    ContentType::Cbor,
+    ContentType::FlexBuffers,
+    ContentType::Json,
+    ContentType::Json5,
+    ContentType::Lexpr,
+    ContentType::MessagePack,
+    ContentType::Pickle,
+    ContentType::Postcard,
+    ContentType::Ron,
+    ContentType::Toml,
+    ContentType::Url,
+    ContentType::Yaml,
+];
+
+fn limit_exceeded(kind: LimitKind, limit: usize) -> Error {
+    Error::LimitExceeded { kind, limit }
+}
+
+#[test]
+fn test_decode_within_limits() {
+    avro::register_schema::<MyStruct>(avro::Schema::parse_str(AVRO_SCHEMA).unwrap());
+    let limits = Limits::new()
+        .max_input_bytes(1024)
+        .max_depth(2)
+        .max_collection_length(16)
+        .max_string_length(64)
+        .max_total_allocation(1024);
+    let options = DecodeOptions::new().limits(limits);
+    for content_type in CONTENT_TYPES {
+        let encoded = MyStruct::default().encode(&content_type).unwrap();
+        let decoded: Decoded<MyStruct> = encoded
+            .decode_with(&content_type, &options)
+            .unwrap_or_else(|e| panic!("{:?}: {}", content_type, e));
+        assert_eq!(decoded.into(), MyStruct::default(), "{:?}", content_type);
+    }
+}
+
+#[test]
+fn test_decode_string_length() {
+    avro::register_schema::<MyStruct>(avro::Schema::parse_str(AVRO_SCHEMA).unwrap());
+    let options = DecodeOptions::new().limits(Limits::new().max_string_length(8));
+    for content_type in CONTENT_TYPES {
+        let encoded = MyStruct::default().encode(&content_type).unwrap();
+        let decoded: crate::Result<Decoded<MyStruct>> =
+            encoded.decode_with(&content_type, &options);
+        assert_eq!(
+            limit_exceeded(LimitKind::StringLength, 8),
+            decoded.map(|_| ()).unwrap_err(),
+            "{:?}",
+            content_type
+        );
+    }
+}
+
+#[test]
+fn test_decode_depth() {
+    let options = DecodeOptions::new().limits(Limits::new().max_depth(2));
+    let decoded: Decoded<Vec<Vec<u8>>> = "[[1], [2]]".decode_with("json", &options).unwrap();
+    assert_eq!(vec![vec![1], vec![2]], decoded.into());
+    let decoded: crate::Result<Decoded<Vec<Vec<Vec<u8>>>>> =
+        "[[[1]]]".decode_with("json", &options);
+    assert_eq!(
+        limit_exceeded(LimitKind::Depth, 2),
+        decoded.map(|_| ()).unwrap_err()
+    );
+}
+
+#[test]
+fn test_decode_collection_length() {
+    let options = DecodeOptions::new().limits(Limits::new().max_collection_length(2));
+    let decoded: crate::Result<Decoded<Vec<u8>>> = "[1, 2, 3]".decode_with("yaml", &options);
+    assert_eq!(
+        limit_exceeded(LimitKind::CollectionLength, 2),
+        decoded.map(|_| ()).unwrap_err()
+    );
+
+    // an array32 declaring u32::MAX items, rejected before any item is read
+    let declared = &[0xdd, 0xff, 0xff, 0xff, 0xff, 0x01][..];
+    let decoded: crate::Result<Decoded<Vec<u8>>> = declared.decode_with("messagepack", &options);
+    assert_eq!(
+        limit_exceeded(LimitKind::CollectionLength, 2),
+        decoded.map(|_| ()).unwrap_err()
+    );
+}
+
+#[test]
+fn test_decode_total_allocation() {
+    let options = DecodeOptions::new().limits(Limits::new().max_total_allocation(12));
+    let strings = vec!["abcd".to_string(); 3];
+    let encoded = strings.encode("cbor").unwrap();
+    let decoded: Decoded<Vec<String>> = encoded.decode_with("cbor", &options).unwrap();
+    assert_eq!(strings, decoded.into());
+
+    let encoded = vec!["abcd".to_string(); 4].encode("cbor").unwrap();
+    let decoded: crate::Result<Decoded<Vec<String>>> = encoded.decode_with("cbor", &options);
+    assert_eq!(
+        limit_exceeded(LimitKind::TotalAllocation, 12),
+        decoded.map(|_| ()).unwrap_err()
+    );
+}
@@ -0,0 +1,175 @@
+use crate::codec::{self, Codec, DecodeVisitor};
+use crate::{ContentType, DecodeOptions, Decoded, Error, MediaType, SimpleDecoder};
+
+#[test]
+fn test_media_type_parse() {
+    let media_type =
+        MediaType::parse("Application/Vnd.Acme+JSON ; Charset=UTF-8;version=2").unwrap();
+    assert_eq!("application", media_type.kind());
+    assert_eq!("vnd.acme+json", media_type.subtype());
+    assert_eq!(Some("json"), media_type.suffix());
+    assert_eq!("application/vnd.acme+json", media_type.essence());
+    assert_eq!(Some("UTF-8"), media_type.charset());
+    assert_eq!(Some("2"), media_type.param("VERSION"));
+    assert_eq!(None, media_type.param("profile"));
+    assert_eq!(
+        vec![("charset", "UTF-8"), ("version", "2")],
+        media_type.params().collect::<Vec<_>>()
+    );
+
+    let media_type: MediaType = r#"application/json; profile="https://a.b/c;d \"e\"""#
+        .parse()
+        .unwrap();
+    assert_eq!(None, media_type.suffix());
+    assert_eq!(Some(r#"https://a.b/c;d "e""#), media_type.param("profile"));
+    assert_eq!(
+        r#"application/json; profile="https://a.b/c;d \"e\"""#,
+        media_type.to_string()
+    );
+    assert_eq!(media_type, media_type.to_string().parse().unwrap());
+}
+
+#[test]
+fn test_media_type_invalid() {
+    for invalid in [
+        "",
+        "json",
+        "application/",
+        "/json",
+        "application/json/x",
+        "application/json; charset",
+        "application/json; charset=",
+        "application/json; =utf-8",
+        "application/json; charset=utf 8",
+        r#"application/json; charset="utf-8"x"#,
+        r#"application/json; charset="utf-8"#,
+    ] {
+        assert_eq!(
+            Err(Error::InvalidMediaType(invalid.to_string())),
+            MediaType::parse(invalid),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_media_type_content_type() {
+    for (media_type, content_type) in [
+        ("application/json; charset=utf-8", ContentType::Json),
+        ("application/x-yaml;charset=\"utf-8\"", ContentType::Yaml),
+        ("application/vnd.acme+json; version=2", ContentType::Json),
+        ("application/problem+cbor", ContentType::Cbor),
+        ("application/ld+yaml", ContentType::Yaml),
+        (
+            "application/vnd.acme.v1+messagepack",
+            ContentType::MessagePack,
+        ),
+    ] {
+        assert_eq!(content_type, ContentType::try_from(media_type).unwrap());
+        let media_type = MediaType::parse(media_type).unwrap();
+        assert_eq!(content_type, ContentType::try_from(media_type).unwrap());
+    }
+    for unknown in [
+        "text/html",
+        "application/vnd.acme+zip",
+        "application/json+",
+        "foobar",
+    ] {
+        assert_eq!(
+            Err(Error::UnknownContentTypeMatchFromStr(unknown.to_string())),
+            ContentType::try_from(unknown)
+        );
+    }
+    let media_type = MediaType::parse("application/vnd.acme+zip").unwrap();
+    assert!(matches!(
+        media_type.content_type(),
+        Err(Error::UnknownContentTypeMatchFromStr(e)) if e == "application/vnd.acme+zip"
+    ));
+}
+
+#[test]
+fn test_media_type_charset() {
+    let decode = |content_type: &str, media_type: &str| {
+        let options = DecodeOptions::new().media_type(MediaType::parse(media_type).unwrap());
+        let decoded: crate::Result<Decoded<String>> =
+            r#""foobar""#.decode_with(content_type, &options);
+        decoded.map(Decoded::into)
+    };
+    assert_eq!(Ok("foobar".to_string()), decode("json", "application/json"));
+    assert_eq!(
+        Ok("foobar".to_string()),
+        decode("json", "application/json; charset=UTF-8")
+    );
+    assert_eq!(
+        Ok("foobar".to_string()),
+        decode("yaml", "application/yaml; charset=us-ascii")
+    );
+    assert_eq!(
+        Err(Error::UnsupportedCharset(
+            ContentType::Json,
+            "iso-8859-1".to_string()
+        )),
+        decode("json", "application/json; charset=iso-8859-1")
+    );
+
+    // binary formats ignore the charset
+    let options = DecodeOptions::new().media_type(MediaType::parse("a/b; charset=latin1").unwrap());
+    let decoded: Decoded<String> = b"\xa6foobar"
+        .as_slice()
+        .decode_with("messagepack", &options)
+        .unwrap();
+    assert_eq!("foobar", *decoded);
+}
+
+struct Versioned;
+
+impl Codec for Versioned {
+    fn mime(&self) -> &str {
+        "application/x-versioned"
+    }
+
+    fn encode(&self, value: &dyn erased_serde::Serialize) -> crate::Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(Error::from)
+    }
+
+    fn decode(&self, data: &[u8], visit: DecodeVisitor) -> crate::Result<()> {
+        let mut de = serde_json::Deserializer::from_slice(data);
+        visit(&mut <dyn erased_serde::Deserializer>::erase(&mut de))
+    }
+
+    fn decode_media_type(
+        &self,
+        data: &[u8],
+        media_type: &MediaType,
+        visit: DecodeVisitor,
+    ) -> crate::Result<()> {
+        match media_type.param("version") {
+            Some("1") | None => self.decode(data, visit),
+            Some(version) => {
+                let e = <erased_serde::Error as serde::de::Error>::custom(version);
+                Err(Error::from(e))
+            }
+        }
+    }
+}
+
+#[test]
+fn test_media_type_codec() {
+    let content_type = codec::register(Versioned, &[]);
+    let decode = |media_type: &str| {
+        let media_type = MediaType::parse(media_type).unwrap();
+        let options = DecodeOptions::new().media_type(media_type);
+        let decoded: crate::Result<Decoded<String>> =
+            r#""foobar""#.decode_with(&content_type, &options);
+        decoded.map(Decoded::into)
+    };
+    assert_eq!(
+        Ok("foobar".to_string()),
+        decode("application/x-versioned; version=1; charset=latin1")
+    );
+    assert!(decode("application/x-versioned; version=2")
+        .unwrap_err()
+        .to_string()
+        .ends_with(": 2"));
+}
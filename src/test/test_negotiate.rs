@@ -0,0 +1,74 @@
+use crate::ContentType;
+
+const SUPPORTED: [ContentType; 3] = [ContentType::Json, ContentType::Cbor, ContentType::Yaml];
+
+fn negotiate(accept: &str) -> Option<ContentType> {
+    ContentType::negotiate(accept, &SUPPORTED)
+}
+
+#[test]
+fn test_negotiate_weights() {
+    let accept = "application/json;q=0.5, application/cbor";
+    assert_eq!(Some(ContentType::Cbor), negotiate(accept));
+    let accept = "application/json;q=0.5, application/cbor;q=0.4";
+    assert_eq!(Some(ContentType::Json), negotiate(accept));
+    let accept = "application/yaml;q=1.000, application/json;q=0.999";
+    assert_eq!(Some(ContentType::Yaml), negotiate(accept));
+    // ties go to the order of the supported content types
+    let accept = "application/yaml, application/cbor";
+    assert_eq!(Some(ContentType::Cbor), negotiate(accept));
+}
+
+#[test]
+fn test_negotiate_wildcards() {
+    assert_eq!(Some(ContentType::Json), negotiate("*/*"));
+    assert_eq!(Some(ContentType::Json), negotiate("application/*"));
+    assert_eq!(Some(ContentType::Json), negotiate(""));
+    let accept = "text/html, application/*;q=0.2, application/yaml";
+    assert_eq!(Some(ContentType::Yaml), negotiate(accept));
+    // any name of the format matches, not only its MIME type
+    assert_eq!(Some(ContentType::Json), negotiate("text/*"));
+    let yaml = ContentType::negotiate("text/*", &[ContentType::Yaml]);
+    assert_eq!(Some(ContentType::Yaml), yaml);
+    assert_eq!(None, negotiate("image/*"));
+    assert_eq!(None, negotiate("text/html"));
+}
+
+#[test]
+fn test_negotiate_precedence() {
+    // the most specific range decides the weight
+    let accept = "*/*, application/json;q=0";
+    assert_eq!(Some(ContentType::Cbor), negotiate(accept));
+    let accept = "application/*;q=0.1, */*;q=0.9, application/cbor;q=0.5";
+    assert_eq!(Some(ContentType::Cbor), negotiate(accept));
+    let accept = "application/json;q=0.1, application/json;charset=utf-8;q=0.8, */*;q=0.5";
+    assert_eq!(Some(ContentType::Json), negotiate(accept));
+    let accept = "application/json;q=0, application/cbor;q=0, application/yaml;q=0";
+    assert_eq!(None, negotiate(accept));
+}
+
+#[test]
+fn test_negotiate_parameters() {
+    // a range with parameters the format does not have does not match it
+    let accept = "application/json;v=2;q=0, application/*";
+    assert_eq!(Some(ContentType::Json), negotiate(accept));
+    let accept = "application/json;v=2, application/cbor;q=0.5";
+    assert_eq!(Some(ContentType::Cbor), negotiate(accept));
+    let accept = "application/json;charset=latin1, application/cbor;q=0.5";
+    assert_eq!(Some(ContentType::Cbor), negotiate(accept));
+    let accept = "application/json;charset=\"UTF-8\", application/cbor;q=0.5";
+    assert_eq!(Some(ContentType::Json), negotiate(accept));
+}
+
+#[test]
+fn test_negotiate_aliases_and_malformed() {
+    assert_eq!(Some(ContentType::Yaml), negotiate("application/x-yaml"));
+    assert_eq!(
+        Some(ContentType::Yaml),
+        negotiate("Application/YAML ; Q=0.5")
+    );
+    // malformed ranges are ignored
+    let accept =
+        "application/json;q=2, application/cbor;q=abc, */json, yaml, application/yaml;q=0.3";
+    assert_eq!(Some(ContentType::Yaml), negotiate(accept));
+}
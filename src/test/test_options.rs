@@ -0,0 +1,238 @@
+use super::MyStruct;
+use crate::{
+    ContentType, DecodeOptions, Decoded, EncodeOptions, Error, LimitKind, SimpleDecoder,
+    SimpleEncoder, TryToString,
+};
+use ron::extensions::Extensions;
+use serde_pickle::DeOptions;
+use std::collections::HashMap;
+
+#[test]
+fn test_encode_with_default_options() {
+    for content_type in [
+        ContentType::Bson,
+        ContentType::Cbor,
+        ContentType::FlexBuffers,
+        ContentType::Json,
+        ContentType::Json5,
+        ContentType::Lexpr,
+        ContentType::MessagePack,
+        ContentType::Pickle,
+        ContentType::Postcard,
+        ContentType::Ron,
+        ContentType::Toml,
+        ContentType::Url,
+        ContentType::Yaml,
+    ] {
+        let encoded = MyStruct::default()
+            .encode_with(&content_type, &EncodeOptions::default())
+            .unwrap();
+        assert_eq!(
+            *MyStruct::default().encode(&content_type).unwrap(),
+            *encoded,
+            "{:?}",
+            content_type
+        );
+    }
+}
+
+#[test]
+fn test_encode_with_pretty_json() {
+    let encoded = MyStruct::default()
+        .encode_with("json", &EncodeOptions::new().pretty(true))
+        .unwrap();
+    assert_eq!(
+        serde_json::to_vec_pretty(&MyStruct::default()).unwrap(),
+        *encoded
+    );
+    let encoded = MyStruct::default()
+        .encode_with("json", &EncodeOptions::new().pretty(true).indent(4))
+        .unwrap();
+    assert!(encoded
+        .try_to_string()
+        .unwrap()
+        .starts_with("{\n    \"unquoted\""));
+}
+
+#[test]
+fn test_encode_with_ron_struct_names() {
+    let options = EncodeOptions::new().ron_struct_names(true);
+    let encoded = MyStruct::default().encode_with("ron", &options).unwrap();
+    let encoded = encoded.try_to_string().unwrap();
+    assert!(encoded.starts_with("MyStruct(unquoted: "));
+    assert!(!encoded.contains('\n'));
+
+    let options = options.pretty(true).indent(2);
+    let encoded = MyStruct::default().encode_with("ron", &options).unwrap();
+    assert!(encoded
+        .try_to_string()
+        .unwrap()
+        .starts_with("MyStruct(\n  unquoted: "));
+    let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_encode_with_toml_pretty_arrays() {
+    let options = EncodeOptions::new().pretty_arrays(true).indent(2);
+    let encoded = MyStruct::default().encode_with("toml", &options).unwrap();
+    assert!(encoded
+        .try_to_string()
+        .unwrap()
+        .contains("andIn = [\n  \"arrays\",\n  \"arrays-2\",\n]"));
+    let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_encode_with_toml_indent_without_pretty() {
+    let options = EncodeOptions::new().indent(8);
+    let encoded = MyStruct::default().encode_with("toml", &options).unwrap();
+    let plain = MyStruct::default().encode("toml").unwrap();
+    assert_eq!(plain.try_to_string(), encoded.try_to_string());
+    assert!(encoded
+        .try_to_string()
+        .unwrap()
+        .contains("andIn = [\"arrays\", \"arrays-2\"]"));
+}
+
+#[test]
+fn test_encode_with_pickle_protocol() {
+    let encoded = MyStruct::default()
+        .encode_with("pickle", &EncodeOptions::new().pickle_protocol(2))
+        .unwrap();
+    assert_eq!(&[0x80, 2], &encoded[..2]);
+    let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+
+    let encoded = MyStruct::default()
+        .encode_with("pickle", &EncodeOptions::new().pickle_protocol(4))
+        .map(|_| ());
+    assert_eq!(
+        Error::UnsupportedOption(ContentType::Pickle, "pickle_protocol(4)".to_string()),
+        encoded.unwrap_err()
+    );
+}
+
+#[test]
+fn test_encode_with_message_pack_struct_map() {
+    let options = EncodeOptions::new().message_pack_struct_map(true);
+    let encoded = MyStruct::default()
+        .encode_with("messagepack", &options)
+        .unwrap();
+    assert_eq!(
+        rmp_serde::to_vec_named(&MyStruct::default()).unwrap(),
+        *encoded
+    );
+    let decoded: Decoded<MyStruct> = encoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_decode_with_default_options() {
+    for content_type in [
+        ContentType::Bson,
+        ContentType::Json,
+        ContentType::MessagePack,
+        ContentType::Pickle,
+        ContentType::Ron,
+        ContentType::Url,
+    ] {
+        let encoded = MyStruct::default().encode(&content_type).unwrap();
+        let decoded: Decoded<MyStruct> = encoded
+            .decode_with(&content_type, &DecodeOptions::default())
+            .unwrap();
+        assert_eq!(decoded.into(), MyStruct::default(), "{:?}", content_type);
+    }
+}
+
+#[test]
+fn test_decode_with_max_input_size() {
+    let encoded = MyStruct::default().encode("json").unwrap();
+    let options = DecodeOptions::new().max_input_size(encoded.len());
+    let decoded: Decoded<MyStruct> = encoded.decode_with("json", &options).unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+
+    let options = DecodeOptions::new().max_input_size(encoded.len() - 1);
+    let decoded: crate::Result<Decoded<MyStruct>> = encoded.decode_with("json", &options);
+    assert_eq!(
+        Error::LimitExceeded {
+            kind: LimitKind::InputBytes,
+            limit: encoded.len() - 1
+        },
+        decoded.map(|_| ()).unwrap_err()
+    );
+}
+
+#[test]
+fn test_decode_with_recursion_limit() {
+    let nested = vec![vec![vec![1u8]]];
+    let encoded = nested.encode("messagepack").unwrap();
+    let options = DecodeOptions::new().recursion_limit(3);
+    let decoded: Decoded<Vec<Vec<Vec<u8>>>> = encoded.decode_with("messagepack", &options).unwrap();
+    assert_eq!(nested, decoded.into());
+    let options = DecodeOptions::new().recursion_limit(2);
+    let decoded: crate::Result<Decoded<Vec<Vec<Vec<u8>>>>> =
+        encoded.decode_with("messagepack", &options);
+    assert!(matches!(
+        decoded,
+        Err(Error::DecodeFailure(e)) if matches!(e.error(), Error::MessagePackDecodeError(_))
+    ));
+
+    let options = DecodeOptions::new().recursion_limit(0);
+    let decoded: Decoded<HashMap<String, String>> = "a[b]=1".decode_with("url", &options).unwrap();
+    assert_eq!("1", decoded["a[b]"]);
+}
+
+#[test]
+fn test_decode_with_url_strict() {
+    let options = DecodeOptions::new().url_strict(false);
+    let decoded: Decoded<HashMap<String, HashMap<String, String>>> =
+        "a%5Bb%5D=1".decode_with("url", &options).unwrap();
+    assert_eq!("1", decoded["a"]["b"]);
+}
+
+#[test]
+fn test_decode_with_ron_extensions() {
+    let options = DecodeOptions::new().ron_extensions(Extensions::IMPLICIT_SOME);
+    let decoded: Decoded<Option<u8>> = "1".decode_with("ron", &options).unwrap();
+    assert_eq!(Some(1), decoded.into());
+    let decoded: crate::Result<Decoded<Option<u8>>> = "1".decode("ron");
+    assert!(decoded.is_err());
+}
+
+#[test]
+fn test_decode_with_pickle_options() {
+    // `cos\nsystem\n.`, a global that is not resolved by serde-pickle
+    let global = &b"\x80\x03cos\nsystem\nq\x00."[..];
+    let decoded: crate::Result<Decoded<serde_pickle::Value>> = global.decode("pickle");
+    assert!(matches!(
+        decoded,
+        Err(Error::DecodeFailure(e)) if matches!(e.error(), Error::PickleError(_))
+    ));
+    let options =
+        DecodeOptions::new().pickle_options(DeOptions::new().replace_unresolved_globals());
+    let decoded: Decoded<serde_pickle::Value> = global.decode_with("pickle", &options).unwrap();
+    assert_eq!(serde_pickle::Value::None, decoded.into());
+}
+
+#[test]
+fn test_decode_with_unsupported_options() {
+    let unsupported = |options: DecodeOptions, option: &str| {
+        let decoded: crate::Result<Decoded<MyStruct>> = "{}".decode_with("json", &options);
+        assert_eq!(
+            Error::UnsupportedOption(ContentType::Json, option.to_string()),
+            decoded.map(|_| ()).unwrap_err()
+        );
+    };
+    unsupported(DecodeOptions::new().recursion_limit(1), "recursion_limit");
+    unsupported(
+        DecodeOptions::new().pickle_options(DeOptions::new()),
+        "pickle_options",
+    );
+    unsupported(DecodeOptions::new().url_strict(true), "url_strict");
+    unsupported(
+        DecodeOptions::new().ron_extensions(Extensions::empty()),
+        "ron_extensions",
+    );
+}
@@ -0,0 +1,149 @@
+use crate::{ContentType, Decoded, Error, Position, SimpleDecoder, SimpleEncoder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Item<P> {
+    price: P,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Order<P> {
+    items: Vec<Item<P>>,
+}
+
+fn order<P: Clone>(first: P, second: P) -> Order<P> {
+    Order {
+        items: vec![Item { price: first }, Item { price: second }],
+    }
+}
+
+fn decode_failure(decoded: crate::Result<Decoded<Order<u32>>>) -> crate::DecodeError {
+    match decoded.map(|_| ()).unwrap_err() {
+        Error::DecodeFailure(e) => *e,
+        e => panic!("expected a decode failure: {}", e),
+    }
+}
+
+#[test]
+fn test_decode_error_path() {
+    for content_type in [
+        ContentType::Bson,
+        ContentType::Cbor,
+        ContentType::FlexBuffers,
+        ContentType::Json,
+        ContentType::Json5,
+        ContentType::Lexpr,
+        ContentType::MessagePack,
+        ContentType::Pickle,
+        ContentType::Ron,
+        ContentType::Toml,
+        ContentType::Url,
+        ContentType::Yaml,
+    ] {
+        let encoded = order("one", "two").encode(&content_type).unwrap();
+        let e = decode_failure(encoded.decode());
+        assert_eq!(".items[0].price", e.path(), "{:?}", content_type);
+        assert_eq!(
+            std::any::type_name::<Order<u32>>(),
+            e.type_name(),
+            "{:?}",
+            content_type
+        );
+    }
+}
+
+#[test]
+fn test_decode_error_path_postcard() {
+    let encoded = order(1u32, 300).encode("postcard").unwrap();
+    let truncated = &encoded[..encoded.len() - 1];
+    let e = decode_failure(truncated.decode("postcard"));
+    assert_eq!(".items[1].price", e.path());
+    assert!(matches!(e.error(), Error::PostcardError(_)));
+    assert_eq!(None, e.position());
+}
+
+#[test]
+fn test_decode_error_position() {
+    let e = decode_failure("{\n  \"items\": [\n    {\"price\": true}\n  ]\n}".decode("json"));
+    assert_eq!(".items[0].price", e.path());
+    assert_eq!(
+        Some(Position::LineColumn {
+            line: 3,
+            column: 18
+        }),
+        e.position()
+    );
+
+    let e = decode_failure("items = [\n  { price = true },\n]".decode("toml"));
+    assert!(matches!(
+        e.position(),
+        Some(Position::LineColumn { line: 2, .. })
+    ));
+
+    let e = decode_failure("items:\n  - price: [1]\n".decode("yaml"));
+    assert!(matches!(
+        e.position(),
+        Some(Position::LineColumn { line: 2, .. })
+    ));
+
+    let encoded = order(1u32, 2).encode("cbor").unwrap();
+    let truncated = &encoded[..encoded.len() - 1];
+    let e = decode_failure(truncated.decode("cbor"));
+    assert_eq!(Some(Position::Offset(truncated.len())), e.position());
+}
+
+#[test]
+fn test_decode_error_display() {
+    let e = decode_failure(r#"{"items": [{"price": true}]}"#.decode("json"));
+    assert_eq!(
+        format!(
+            "Failed decoding {} at `.items[0].price`: \
+             JSON encoder/decoder error: invalid type: boolean `true`, expected u32 at line 1 column 25",
+            std::any::type_name::<Order<u32>>()
+        ),
+        e.to_string()
+    );
+}
+
+#[test]
+fn test_decode_error_path_enum_and_map() {
+    #[derive(Deserialize, Debug)]
+    enum Shape {
+        Circle { radius: u32 },
+    }
+    let decoded: Decoded<Vec<Shape>> = r#"[{"Circle": {"radius": 1}}]"#.decode("json").unwrap();
+    assert!(matches!(decoded[0], Shape::Circle { radius: 1 }));
+    let decoded: crate::Result<Decoded<Vec<Shape>>> =
+        r#"[{"Circle": {"radius": "big"}}]"#.decode("json");
+    match decoded.map(|_| ()).unwrap_err() {
+        Error::DecodeFailure(e) => assert_eq!(".[0].Circle.radius", e.path()),
+        e => panic!("expected a decode failure: {}", e),
+    }
+
+    let decoded: crate::Result<Decoded<std::collections::HashMap<u32, bool>>> =
+        r#"{"7": 1}"#.decode("json");
+    match decoded.map(|_| ()).unwrap_err() {
+        Error::DecodeFailure(e) => assert_eq!(r#"."7""#, e.path()),
+        e => panic!("expected a decode failure: {}", e),
+    }
+}
+
+#[test]
+fn test_decode_error_path_quotes_keys() {
+    for (key, path) in [
+        ("a.b", r#"."a.b""#),
+        ("x[0]", r#"."x[0]""#),
+        ("", ".\"\""),
+        ("with space", r#"."with space""#),
+        ("quote\"", r#"."quote\"""#),
+        ("snake_case1", ".snake_case1"),
+    ] {
+        let input = format!("{{{:?}: true}}", key);
+        let decoded: crate::Result<Decoded<std::collections::HashMap<String, u32>>> =
+            input.decode("json");
+        match decoded.map(|_| ()).unwrap_err() {
+            Error::DecodeFailure(e) => assert_eq!(path, e.path(), "{}", key),
+            e => panic!("expected a decode failure: {}", e),
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use super::{foo, Foo};
+use crate::{
+    decode_all, decode_all_with, decode_stream, decode_stream_with, encode_all, encode_stream,
+    ContentType, DecodeOptions, Decoded, Error, ErrorKind, LimitKind, Limits, SimpleDecoder,
+    SimpleEncoder, TryToString,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read};
+
+/// Fails once `data` has been read.
+struct Failing<'a> {
+    data: &'a [u8],
+}
+
+impl Read for Failing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.data.read(buf)? {
+            0 => Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset")),
+            read => Ok(read),
+        }
+    }
+}
+
+#[test]
+fn test_json_lines() {
+    for name in [
+        "jsonl",
+        "application/x-ndjson",
+        "application/jsonl",
+        "ndjson",
+    ] {
+        assert_eq!(ContentType::JsonLines, ContentType::try_from(name).unwrap());
+    }
+    assert_eq!(
+        Some(ContentType::JsonLines),
+        ContentType::from_path("a.ndjson")
+    );
+
+    let encoded = foo("foobar").encode("jsonl").unwrap();
+    assert_eq!("{\"bar\":\"foobar\"}\n", encoded.try_to_string().unwrap());
+    let decoded: Decoded<Foo> = encoded.decode().unwrap();
+    assert_eq!(foo("foobar"), decoded.into());
+
+    let mut writer = Vec::new();
+    foo("foobar")
+        .encode_to_writer(&mut writer, "jsonl")
+        .unwrap();
+    assert_eq!(*encoded, writer);
+
+    let decoded: crate::Result<Decoded<Foo>> = b"{\"bar\":\"a\"}\n{\"bar\":\"b\"}\n"
+        .as_slice()
+        .decode("jsonl");
+    assert!(decoded.is_err());
+}
+
+#[test]
+fn test_encode_stream() {
+    let mut writer = Vec::new();
+    let foos = vec![foo("a"), foo("b"), foo("c")];
+    encode_stream(&mut writer, &foos, ContentType::JsonLines).unwrap();
+    assert_eq!(
+        "{\"bar\":\"a\"}\n{\"bar\":\"b\"}\n{\"bar\":\"c\"}\n",
+        String::from_utf8(writer.clone()).unwrap()
+    );
+    let decoded: Vec<Foo> = decode_stream(writer.as_slice(), "jsonl")
+        .unwrap()
+        .collect::<crate::Result<_>>()
+        .unwrap();
+    assert_eq!(foos, decoded);
+
+    let mut writer = Vec::new();
+    encode_stream(&mut writer, Vec::<Foo>::new(), "jsonl").unwrap();
+    assert!(writer.is_empty());
+}
+
+#[test]
+fn test_decode_stream_lines() {
+    let input = "\n{\"bar\": \"a\"}\r\n  \n{\"bar\": 1}\n{\"bar\"\n{\"bar\": \"b\"}";
+    let decoded: Vec<crate::Result<Foo>> =
+        decode_stream(input.as_bytes(), "jsonl").unwrap().collect();
+    assert_eq!(4, decoded.len());
+    assert_eq!(&foo("a"), decoded[0].as_ref().unwrap());
+    assert_eq!(&foo("b"), decoded[3].as_ref().unwrap());
+    for (i, index, line, kind) in [(1, 1, 4, ErrorKind::Data), (2, 2, 5, ErrorKind::Eof)] {
+        let e = match &decoded[i] {
+            Err(Error::RecordFailure(e)) => e,
+            other => panic!("expected a record failure, got {:?}", other),
+        };
+        assert_eq!(index, e.index());
+        assert_eq!(Some(line), e.line());
+        assert_eq!(&ContentType::JsonLines, e.content_type());
+        assert_eq!(kind, decoded[i].as_ref().unwrap_err().kind());
+        assert!(matches!(e.error(), Error::DecodeFailure(_)));
+        let message = e.to_string();
+        assert!(message.starts_with(&format!(
+            "Failed decoding record {} on line {}: ",
+            index, line
+        )));
+    }
+}
+
+#[test]
+fn test_decode_stream_read_failure() {
+    let reader = Failing {
+        data: b"{\"bar\": \"a\"}\n",
+    };
+    let mut decoded = decode_stream::<Foo, _, _>(reader, "jsonl").unwrap();
+    assert_eq!(foo("a"), decoded.next().unwrap().unwrap());
+    assert!(matches!(decoded.next(), Some(Err(Error::IoError(_)))));
+    assert!(decoded.next().is_none());
+}
+
+#[test]
+fn test_stream_unsupported() {
+    let decoded = decode_stream::<Foo, _, _>(&b""[..], "json").map(|_| ());
+    assert_eq!(
+        Err(Error::StreamingNotSupported(ContentType::Json)),
+        decoded
+    );
+    let mut writer = Vec::new();
+    let encoded = encode_stream(&mut writer, [foo("a")], "toml");
+    assert_eq!(
+        Err(Error::StreamingNotSupported(ContentType::Toml)),
+        encoded
+    );
+    assert_eq!(
+        ErrorKind::Unsupported,
+        Error::StreamingNotSupported(ContentType::Toml).kind()
+    );
+}
+
+#[test]
+fn test_yaml_documents() {
+    let foos = vec![foo("a"), foo("b\nc"), foo("d")];
+    let encoded = encode_all(&foos, "yaml").unwrap();
+    assert_eq!(Some(&ContentType::Yaml), encoded.content_type());
+    assert_eq!(
+        "bar: a\n---\nbar: |-\n  b\n  c\n---\nbar: d\n",
+        encoded.try_to_string().unwrap()
+    );
+    assert_eq!(foos, decode_all::<Foo, _>(&encoded, "yaml").unwrap());
+
+    let mut writer = Vec::new();
+    encode_stream(&mut writer, &foos, "yml").unwrap();
+    assert_eq!(*encoded, writer);
+
+    let input = "# manifests\n---\nbar: a\n--- # second\nbar: |\n  b\n---\n---\n\n--- {bar: c}\n...\nbar: d\n...\n";
+    let decoded = decode_all::<Foo, _>(input.as_bytes(), "yaml").unwrap();
+    assert_eq!(vec![foo("a"), foo("b\n"), foo("c"), foo("d")], decoded);
+
+    // directives and comments stay with the document following them
+    let decoded = decode_all::<Foo, _>(b"%YAML 1.2\n---\nbar: foo\n", "yaml").unwrap();
+    assert_eq!(vec![foo("foo")], decoded);
+    let input = "bar: a\n...\n# b\n%YAML 1.2\n%TAG ! tag:example.com,2000:\n---\nbar: b\n";
+    let decoded = decode_all::<Foo, _>(input.as_bytes(), "yaml").unwrap();
+    assert_eq!(vec![foo("a"), foo("b")], decoded);
+
+    assert!(decode_all::<Foo, _>(b"", "yaml").unwrap().is_empty());
+    assert!(decode_all::<Foo, _>(b"---\n# nothing\n", "yaml")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_yaml_document_errors() {
+    let input = "bar: a\n---\nbaz: 1\n---\nbar: [b\n---\n\nbar: c\n";
+    let decoded: Vec<crate::Result<Foo>> =
+        decode_stream(input.as_bytes(), "yaml").unwrap().collect();
+    assert_eq!(4, decoded.len());
+    assert_eq!(&foo("a"), decoded[0].as_ref().unwrap());
+    assert_eq!(&foo("c"), decoded[3].as_ref().unwrap());
+    for (i, line) in [(1, 2), (2, 4)] {
+        match &decoded[i] {
+            Err(Error::RecordFailure(e)) => {
+                assert_eq!(i, e.index());
+                assert_eq!(Some(line), e.line());
+                assert_eq!(&ContentType::Yaml, e.content_type());
+            }
+            other => panic!("expected a record failure, got {:?}", other),
+        }
+    }
+
+    match decode_all::<Foo, _>(input.as_bytes(), "yaml") {
+        Err(Error::RecordFailure(e)) => assert_eq!(1, e.index()),
+        other => panic!("expected a record failure, got {:?}", other),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Baz {
+    baz: u32,
+}
+
+#[test]
+fn test_binary_streams() {
+    let foos = vec![foo("a"), foo("b"), foo("c")];
+    for content_type in [
+        ContentType::Bson,
+        ContentType::Cbor,
+        ContentType::MessagePack,
+        ContentType::Postcard,
+    ] {
+        let encoded = encode_all(&foos, &content_type).unwrap();
+        let decoded = decode_all::<Foo, _>(&encoded, &content_type).unwrap();
+        assert_eq!(foos, decoded, "{:?}", content_type);
+        assert!(decode_all::<Foo, _>(b"", &content_type).unwrap().is_empty());
+
+        // a record of another type fails on its own
+        let mut data = Vec::new();
+        encode_stream(&mut data, [foo("a")], &content_type).unwrap();
+        encode_stream(&mut data, [Baz { baz: 1 }], &content_type).unwrap();
+        encode_stream(&mut data, [foo("c")], &content_type).unwrap();
+        let decoded: Vec<crate::Result<Foo>> = decode_stream(data.as_slice(), &content_type)
+            .unwrap()
+            .collect();
+        assert_eq!(3, decoded.len(), "{:?}", content_type);
+        assert_eq!(&foo("a"), decoded[0].as_ref().unwrap());
+        assert_eq!(&foo("c"), decoded[2].as_ref().unwrap());
+        match &decoded[1] {
+            Err(Error::RecordFailure(e)) => {
+                assert_eq!(1, e.index());
+                assert_eq!(None, e.line());
+                assert!(matches!(e.error(), Error::DecodeFailure(_)));
+            }
+            other => panic!("expected a record failure, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_binary_stream_framing() {
+    // RFC 8742 CBOR sequence of 1, "a" and [2]
+    let decoded = decode_all::<serde_cbor::Value, _>(b"\x01\x61a\x81\x02", "cbor").unwrap();
+    assert_eq!(3, decoded.len());
+    let decoded = decode_all::<u32, _>(b"\x01\x02\xcd\x01\x00", "messagepack").unwrap();
+    assert_eq!(vec![1, 2, 256], decoded);
+
+    // a truncated value ends the stream
+    let mut decoded = decode_stream::<u32, _, _>(&b"\x01\x19\x01"[..], "cbor").unwrap();
+    assert_eq!(1, decoded.next().unwrap().unwrap());
+    match decoded.next() {
+        Some(Err(Error::RecordFailure(e))) => {
+            assert_eq!(1, e.index());
+            assert_eq!(ErrorKind::Eof, e.error().kind());
+        }
+        other => panic!("expected a record failure, got {:?}", other),
+    }
+    assert!(decoded.next().is_none());
+
+    let mut decoded = decode_stream::<Foo, _, _>(&b"\x04\x00\x00\x00"[..], "bson").unwrap();
+    match decoded.next() {
+        Some(Err(Error::RecordFailure(e))) => assert_eq!(
+            &Error::InvalidFrame(
+                ContentType::Bson,
+                "document length 4 is too short".to_string()
+            ),
+            e.error()
+        ),
+        other => panic!("expected a record failure, got {:?}", other),
+    }
+    assert!(decoded.next().is_none());
+    // truncated documents are record failures, not failures reading the input
+    for data in [&b"\x20\x00\x00\x00\x02"[..], &b"\x20\x00"[..]] {
+        match decode_all::<Foo, _>(data, "bson") {
+            Err(Error::RecordFailure(e)) => {
+                assert_eq!(0, e.index());
+                assert_eq!(ErrorKind::Eof, e.error().kind());
+            }
+            other => panic!("expected a record failure, got {:?}", other),
+        }
+    }
+
+    // postcard frames are zero terminated, so decoding resumes after an invalid one
+    let mut data = vec![0, 0x05, 0x01, 0];
+    encode_stream(&mut data, [foo("a")], "postcard").unwrap();
+    let decoded: Vec<crate::Result<Foo>> = decode_stream(data.as_slice(), "postcard")
+        .unwrap()
+        .collect();
+    assert_eq!(2, decoded.len());
+    match &decoded[0] {
+        Err(Error::RecordFailure(e)) => {
+            assert_eq!(0, e.index());
+            assert_eq!(ErrorKind::Syntax, e.error().kind());
+        }
+        other => panic!("expected a record failure, got {:?}", other),
+    }
+    assert_eq!(&foo("a"), decoded[1].as_ref().unwrap());
+}
+
+#[test]
+fn test_decode_stream_limits() {
+    let options = DecodeOptions::new().limits(Limits::new().max_input_bytes(16));
+    let exceeded = |decoded: Option<crate::Result<Foo>>| match decoded {
+        Some(Err(Error::RecordFailure(e))) => assert_eq!(
+            &Error::LimitExceeded {
+                kind: LimitKind::InputBytes,
+                limit: 16
+            },
+            e.error()
+        ),
+        other => panic!("expected a record failure, got {:?}", other),
+    };
+
+    // the limit applies to every record, not the stream
+    let foos: Vec<Foo> = ["a", "b", "c", "d", "e", "f"].map(foo).into();
+    for content_type in ["jsonl", "yaml", "bson", "cbor", "messagepack", "postcard"] {
+        let encoded = encode_all(&foos, content_type).unwrap();
+        assert!(encoded.len() > 16);
+        let decoded = decode_all_with::<Foo, _>(&encoded, content_type, &options).unwrap();
+        assert_eq!(foos, decoded, "{}", content_type);
+
+        let encoded = encode_all([foo("a"), foo(&"b".repeat(16))], content_type).unwrap();
+        let mut decoded = decode_stream_with(encoded.as_slice(), content_type, &options).unwrap();
+        assert_eq!(
+            foo("a"),
+            decoded.next().unwrap().unwrap(),
+            "{}",
+            content_type
+        );
+        exceeded(decoded.next());
+        assert!(decoded.next().is_none(), "{}", content_type);
+    }
+
+    // rejected without reading the rest of the input
+    let reader = io::repeat(b' ').take(1 << 30);
+    let reader = b"{\"bar\": \"a\"".chain(reader);
+    let mut decoded = decode_stream_with::<Foo, _, _>(reader, "jsonl", &options).unwrap();
+    exceeded(decoded.next());
+    let reader = b"\xff\xff\xff\x7f".chain(io::repeat(0).take(1 << 30));
+    let mut decoded = decode_stream_with::<Foo, _, _>(reader, "bson", &options).unwrap();
+    exceeded(decoded.next());
+}
@@ -2,6 +2,7 @@ use super::super::Error;
 #[cfg(not(tarpaulin_include))]
 impl Eq for Error {}
 #[cfg(not(tarpaulin_include))]
+#[allow(clippy::match_like_matches_macro, clippy::useless_format)]
 impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
         match self {
@@ -17,6 +18,22 @@ impl PartialEq for Error {
                 Error::UnknownContentTypeMatchFromStr(ee) => format!("{}", e) == format!("{}", ee),
                 _ => false,
             },
+            Error::IoError(e) => match other {
+                Error::IoError(ee) => format!("{}", e) == format!("{}", ee),
+                _ => false,
+            },
+            Error::AvroError(e) => match other {
+                Error::AvroError(ee) => format!("{}", e) == format!("{}", ee),
+                _ => false,
+            },
+            Error::AvroSchemaNotRegistered(e) => match other {
+                Error::AvroSchemaNotRegistered(ee) => e == ee,
+                _ => false,
+            },
+            Error::AvroUnexpectedRecordCount(e) => match other {
+                Error::AvroUnexpectedRecordCount(ee) => e == ee,
+                _ => false,
+            },
             Error::BsonSerializationFailure(e) => match other {
                 Error::BsonSerializationFailure(ee) => format!("{}", e) == format!("{}", ee),
                 _ => false,
@@ -92,12 +109,83 @@ impl PartialEq for Error {
                 Error::XmlError(ee) => format!("{:?}", e) == format!("{:?}", ee),
                 _ => false,
             },
+            Error::CodecFailure(e) => match other {
+                Error::CodecFailure(ee) => format!("{}", e) == format!("{}", ee),
+                _ => false,
+            },
+            Error::EncodeFailure(e) => match other {
+                Error::EncodeFailure(ee) => {
+                    e.content_type() == ee.content_type()
+                        && e.type_name() == ee.type_name()
+                        && e.error() == ee.error()
+                }
+                _ => false,
+            },
+            Error::DecodeFailure(e) => match other {
+                Error::DecodeFailure(ee) => {
+                    e.path() == ee.path()
+                        && e.position() == ee.position()
+                        && e.type_name() == ee.type_name()
+                        && e.error() == ee.error()
+                }
+                _ => false,
+            },
+            Error::RecordFailure(e) => match other {
+                Error::RecordFailure(ee) => {
+                    e.index() == ee.index() && e.line() == ee.line() && e.error() == ee.error()
+                }
+                _ => false,
+            },
+            Error::UnsupportedOption(e, o) => match other {
+                Error::UnsupportedOption(ee, oo) => e == ee && o == oo,
+                _ => false,
+            },
+            Error::InvalidMediaType(e) => match other {
+                Error::InvalidMediaType(ee) => e == ee,
+                _ => false,
+            },
+            Error::UnsupportedCharset(e, c) => match other {
+                Error::UnsupportedCharset(ee, cc) => e == ee && c == cc,
+                _ => false,
+            },
+            Error::LimitExceeded { kind, limit } => match other {
+                Error::LimitExceeded {
+                    kind: other_kind,
+                    limit: other_limit,
+                } => kind == other_kind && limit == other_limit,
+                _ => false,
+            },
+            Error::ContentTypeNotDetected => matches!(other, Error::ContentTypeNotDetected),
+            Error::UnknownExtension(e) => match other {
+                Error::UnknownExtension(ee) => e == ee,
+                _ => false,
+            },
+            Error::NotAcceptable(e) => match other {
+                Error::NotAcceptable(ee) => e == ee,
+                _ => false,
+            },
             Error::TypeDoesNotSupportSerialization(e) => match other {
                 Error::TypeDoesNotSupportSerialization(ee) => {
                     format!("{:?}", e) == format!("{:?}", ee)
                 }
                 _ => false,
             },
+            Error::BorrowedDecodingNotSupported(e) => match other {
+                Error::BorrowedDecodingNotSupported(ee) => e == ee,
+                _ => false,
+            },
+            Error::TranscodingNotSupported(e) => match other {
+                Error::TranscodingNotSupported(ee) => e == ee,
+                _ => false,
+            },
+            Error::StreamingNotSupported(e) => match other {
+                Error::StreamingNotSupported(ee) => e == ee,
+                _ => false,
+            },
+            Error::InvalidFrame(e, f) => match other {
+                Error::InvalidFrame(ee, ff) => e == ee && f == ff,
+                _ => false,
+            },
             #[cfg(feature = "http")]
             Error::FailedConvertingHeaderValueToContentType(e) => match other {
                 Error::FailedConvertingHeaderValueToContentType(ee) => {
@@ -117,3 +205,31 @@ impl PartialEq for Error {
         }
     }
 }
+
+#[test]
+fn test_error_send_sync_clone() {
+    fn assert_impl<T: std::error::Error + Send + Sync + Clone + 'static>() {}
+    assert_impl::<Error>();
+    assert_impl::<crate::DecodeError>();
+}
+
+#[test]
+fn test_error_source() {
+    use crate::{Decoded, SimpleDecoder};
+    use std::error::Error as _;
+
+    let decoded: crate::Result<Decoded<u32>> = r#""one""#.decode("json");
+    let e = decoded.map(|_| ()).unwrap_err();
+    let source = e.source().unwrap();
+    assert!(matches!(source.downcast_ref(), Some(Error::JsonError(_))));
+    let backend = source.source().unwrap();
+    assert!(backend
+        .downcast_ref::<serde_json::Error>()
+        .unwrap()
+        .is_data());
+
+    let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(e.clone());
+    assert_eq!(e.to_string(), boxed.to_string());
+    assert_eq!(e, e.clone());
+    assert!(Error::ContentTypeNotDetected.source().is_none());
+}
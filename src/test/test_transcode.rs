@@ -0,0 +1,100 @@
+use super::test_constants::*;
+use super::{foo, MyStruct};
+use crate::{transcode, ContentType, Decoded, Error, SimpleEncoder};
+use std::ops::Deref;
+
+#[test]
+fn test_transcode_yaml_to_messagepack() {
+    let transcoded = transcode(
+        EXAMPLE_YAML_DESERIALIZE.as_bytes(),
+        "yaml",
+        "application/messagepack",
+    )
+    .unwrap();
+    let decoded: Decoded<MyStruct> = transcoded.decode().unwrap();
+    assert_eq!(decoded.into(), MyStruct::default());
+}
+
+#[test]
+fn test_transcode_json_to_toml() {
+    let transcoded = transcode(EXAMPLE_JSON_DESERIALIZE.as_bytes(), "json", "toml").unwrap();
+    assert_eq!(TOML_SERIALIZE.as_bytes(), transcoded.deref());
+}
+
+#[test]
+fn test_transcode_self_describing_formats() {
+    for from in [
+        ContentType::Bson,
+        ContentType::Cbor,
+        ContentType::FlexBuffers,
+        ContentType::Json,
+        ContentType::Json5,
+        ContentType::Pickle,
+        ContentType::Ron,
+        ContentType::Toml,
+        ContentType::Yaml,
+    ] {
+        let input = MyStruct::default().encode(&from).unwrap();
+        let transcoded = transcode(&input, &from, "json").unwrap();
+        let decoded: Decoded<MyStruct> = transcoded.decode().unwrap();
+        assert_eq!(decoded.into(), MyStruct::default(), "{:?}", from);
+    }
+}
+
+#[test]
+fn test_transcode_target_errors() {
+    let target_error = |input: &[u8], to| match transcode(input, "json", to).map(|_| ()) {
+        Err(Error::EncodeFailure(e)) => e.into_error(),
+        other => panic!("expected an encode failure, got {:?}", other),
+    };
+    assert!(matches!(
+        target_error(br#"{"foo": null}"#, "toml"),
+        Error::TomlSerializationFailure(toml::ser::Error::UnsupportedType)
+    ));
+    assert!(matches!(
+        target_error(br#"{"foo": 18446744073709551615}"#, "bson"),
+        Error::BsonSerializationFailure(bson::ser::Error::UnsignedIntegerExceededRange(u64::MAX))
+    ));
+}
+
+#[test]
+fn test_transcode_source_errors() {
+    assert!(matches!(
+        transcode(br#"{"foo": [1, }"#, "json", "yaml").map(|_| ()),
+        Err(Error::JsonError(_))
+    ));
+    assert!(matches!(
+        transcode(b"foo: [1", "yaml", "json").map(|_| ()),
+        Err(Error::YamlError(_))
+    ));
+    // trailing input after the last value
+    assert!(matches!(
+        transcode(b"foo = 1\n]", "toml", "json").map(|_| ()),
+        Err(Error::TomlDeserializationFailure(_))
+    ));
+    let mut message_pack = foo("foo").encode("messagepack").unwrap().to_vec();
+    message_pack.extend_from_slice(&foo("bar").encode("messagepack").unwrap());
+    assert!(matches!(
+        transcode(&message_pack, "messagepack", "json").map(|_| ()),
+        Err(Error::MessagePackDecodeError(_))
+    ));
+    assert!(matches!(
+        transcode(b"{bar: 'foo'} {bar: 'bar'}", "json5", "json").map(|_| ()),
+        Err(Error::Json5Error(_))
+    ));
+}
+
+#[test]
+fn test_transcode_unsupported() {
+    let postcard = MyStruct::default().encode("postcard").unwrap();
+    assert_eq!(
+        Error::TranscodingNotSupported(ContentType::Postcard),
+        transcode(&postcard, "postcard", "json")
+            .map(|_| ())
+            .unwrap_err()
+    );
+    assert_eq!(
+        Error::TranscodingNotSupported(ContentType::Avro),
+        transcode(b"{}", "json", "avro").map(|_| ()).unwrap_err()
+    );
+}
@@ -0,0 +1,368 @@
+//! # Transcode
+//! Converting between two formats without a Rust type in between. The input is streamed from the
+//! deserializer of the source format directly into the serializer of the target format, in the
+//! same way as `serde-transcode`.
+//!
+//! Unlike `serde-transcode`, errors keep their type: Errors raised by the target format are
+//! returned as `Error::EncodeFailure` holding the error of the target format, e.g. TOML rejecting
+//! `null` as `Error::TomlSerializationFailure`, or BSON rejecting an `u64` out of range as
+//! `Error::BsonSerializationFailure`. Errors raised while reading the input are returned as the
+//! error of the source format.
+//!
+//! Some formats are not able to take part in transcoding:
+//! - Avro, as a schema is needed both for reading and writing.
+//! - Postcard as source, as it is not self-describing.
+//!
+//! Formats registered through `codec` can be used both as source and target.
+//!
+//! Url and Lexpr are read into an intermediate `serde_json::Value` as their backends do not expose
+//! a deserializer.
+//!
+//! ```rust
+//! use simple_serde::{transcode, TryToString};
+//!
+//! let yaml = "bar: foobar\n";
+//! let json = transcode(yaml.as_bytes(), "yaml", "json").unwrap();
+//! assert_eq!(r#"{"bar":"foobar"}"#, json.try_to_string().unwrap());
+//! ```
+
+use crate::codec::{self, Codec};
+use crate::{ContentType, Encoded, Error, Result, SimpleEncoder};
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess};
+use serde::ser::{self, Error as _, Serialize, SerializeMap, SerializeSeq, Serializer};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+
+/// Transcode `input` from one format to another, see the module documentation for details.
+pub fn transcode<
+    F: TryInto<ContentType, Error = impl Into<Error>>,
+    T: TryInto<ContentType, Error = impl Into<Error>>,
+>(
+    input: &[u8],
+    from: F,
+    to: T,
+) -> Result<Encoded> {
+    let from = from.try_into().map_err(|e| e.into())?;
+    let to = to.try_into().map_err(|e| e.into())?;
+    if to == ContentType::Avro {
+        return Err(Error::TranscodingNotSupported(to));
+    }
+    let bson = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        let document: bson::Document = bson::from_slice(o)?;
+        transcode_from(bson::Deserializer::new(bson::Bson::Document(document)), to)
+    };
+    let cbor = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        let mut de = serde_cbor::Deserializer::from_slice(o);
+        let encoded = transcode_from(&mut de, to)?;
+        de.end().map(|_| encoded).map_err(Error::from)
+    };
+    let flexbuffers = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        let reader =
+            flexbuffers::Reader::get_root(o).map_err(flexbuffers::DeserializationError::Reader)?;
+        transcode_from(reader, to)
+    };
+    let json = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        let mut de = serde_json::Deserializer::from_slice(o);
+        let encoded = transcode_from(&mut de, to)?;
+        de.end().map(|_| encoded).map_err(Error::from)
+    };
+    let json5 = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        transcode_from(
+            &mut json5::Deserializer::from_str(std::str::from_utf8(o)?)?,
+            to,
+        )
+    };
+    let lexpr = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        serde_lexpr::from_slice::<serde_json::Value>(o)?.encode(to)
+    };
+    let message_pack = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        // read through a cursor, as the deserializer over a slice does not tell where it stopped
+        let mut de = rmp_serde::Deserializer::new(std::io::Cursor::new(o));
+        let encoded = transcode_from(&mut de, to)?;
+        match de.position() as usize {
+            position if position < o.len() => Err(rmp_serde::decode::Error::Syntax(format!(
+                "trailing bytes at offset {}",
+                position
+            ))
+            .into()),
+            _ => Ok(encoded),
+        }
+    };
+    let pickle = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        let mut de = serde_pickle::Deserializer::new(o, Default::default());
+        let encoded = transcode_from(&mut de, to)?;
+        de.end().map(|_| encoded).map_err(Error::from)
+    };
+    let ron = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        let mut de = ron::Deserializer::from_bytes(o)?;
+        let encoded = transcode_from(&mut de, to)?;
+        de.end().map(|_| encoded).map_err(Error::from)
+    };
+    let toml = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        // the document is parsed as a whole, so trailing input fails without calling the
+        // deprecated `Deserializer::end`
+        let mut de = toml::Deserializer::new(std::str::from_utf8(o)?);
+        transcode_from(&mut de, to)
+    };
+    let url = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        serde_qs::from_bytes::<serde_json::Value>(o)?.encode(to)
+    };
+    let yaml = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        transcode_from(serde_yaml::Deserializer::from_slice(o), to)
+    };
+    #[cfg(feature = "accept-limited-xml-serialize")]
+    let xml = |o: &[u8], to: ContentType| -> Result<Encoded> {
+        transcode_from(
+            &mut crate::prelude::xml::Deserializer::new_from_reader(o),
+            to,
+        )
+    };
+    let custom = |o: &[u8], codec: &dyn Codec, to: ContentType| -> Result<Encoded> {
+        codec::visit(codec, o, None, |de| transcode_from(de, to.clone()))
+    };
+    match from {
+        ContentType::Avro => Err(Error::TranscodingNotSupported(from)),
+        ContentType::Bson => bson(input, to),
+        ContentType::Cbor => cbor(input, to),
+        ContentType::FlexBuffers => flexbuffers(input, to),
+        ContentType::Json => json(input, to),
+        ContentType::Json5 => json5(input, to),
+        ContentType::JsonLines => json(input, to),
+        ContentType::Lexpr => lexpr(input, to),
+        ContentType::MessagePack => message_pack(input, to),
+        ContentType::Pickle => pickle(input, to),
+        ContentType::Postcard => Err(Error::TranscodingNotSupported(from)),
+        ContentType::Ron => ron(input, to),
+        ContentType::Toml => toml(input, to),
+        ContentType::Url => url(input, to),
+        ContentType::Yaml => yaml(input, to),
+        #[cfg(feature = "accept-limited-xml-serialize")]
+        ContentType::Xml => xml(input, to),
+        ContentType::Custom(codec) => custom(input, codec.as_ref(), to),
+    }
+}
+
+fn transcode_from<'de, D>(deserializer: D, to: ContentType) -> Result<Encoded>
+where
+    D: Deserializer<'de>,
+    D::Error: Into<Error>,
+{
+    let transcoder = Transcoder::new(deserializer);
+    transcoder
+        .encode(to)
+        .map_err(|e| transcoder.take_error().map(Into::into).unwrap_or(e))
+}
+
+/// Serializes whatever the deserializer yields. Errors from the deserializer are kept in `error`,
+/// so they can be returned with their type instead of being converted into a serializer error.
+struct Transcoder<D, E> {
+    deserializer: RefCell<Option<D>>,
+    error: Cell<Option<E>>,
+}
+
+impl<'de, D> Transcoder<D, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    fn new(deserializer: D) -> Self {
+        Transcoder {
+            deserializer: RefCell::new(Some(deserializer)),
+            error: Cell::new(None),
+        }
+    }
+
+    fn take_error(&self) -> Option<D::Error> {
+        self.error.take()
+    }
+
+    /// Hand the result of serializing this transcoder back to the deserializer driving it. An
+    /// error from the serializer is kept in `target_error` unless it was caused by the
+    /// deserializer of this transcoder.
+    fn forward<T, S: ser::Error>(
+        &self,
+        target_error: &Cell<Option<S>>,
+        result: std::result::Result<T, S>,
+    ) -> std::result::Result<T, D::Error> {
+        result.map_err(|e| match self.take_error() {
+            Some(source) => source,
+            None => keep(target_error, e),
+        })
+    }
+}
+
+impl<'de, D> Serialize for Transcoder<D, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let target_error = Cell::new(None);
+        self.deserializer
+            .borrow_mut()
+            .take()
+            .expect("Transcoder may only be serialized once")
+            .deserialize_any(Visitor {
+                serializer,
+                error: &target_error,
+            })
+            .map_err(|e| {
+                target_error.take().unwrap_or_else(|| {
+                    let error = S::Error::custom(&e);
+                    self.error.set(Some(e));
+                    error
+                })
+            })
+    }
+}
+
+/// Keep the serializer error in `slot`, and return an error unwinding the deserializer.
+fn keep<S: ser::Error, E: de::Error>(slot: &Cell<Option<S>>, error: S) -> E {
+    let unwind = E::custom(&error);
+    slot.set(Some(error));
+    unwind
+}
+
+struct Visitor<'a, S: Serializer> {
+    serializer: S,
+    error: &'a Cell<Option<S::Error>>,
+}
+
+macro_rules! forward_visit {
+    ($($visit:ident($ty:ty) => $serialize:ident,)*) => {
+        $(
+            fn $visit<E: de::Error>(self, v: $ty) -> std::result::Result<S::Ok, E> {
+                self.serializer.$serialize(v).map_err(|e| keep(self.error, e))
+            }
+        )*
+    };
+}
+
+impl<'de, 'a, S: Serializer> de::Visitor<'de> for Visitor<'a, S> {
+    type Value = S::Ok;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "any value")
+    }
+
+    forward_visit! {
+        visit_bool(bool) => serialize_bool,
+        visit_i8(i8) => serialize_i8,
+        visit_i16(i16) => serialize_i16,
+        visit_i32(i32) => serialize_i32,
+        visit_i64(i64) => serialize_i64,
+        visit_i128(i128) => serialize_i128,
+        visit_u8(u8) => serialize_u8,
+        visit_u16(u16) => serialize_u16,
+        visit_u32(u32) => serialize_u32,
+        visit_u64(u64) => serialize_u64,
+        visit_u128(u128) => serialize_u128,
+        visit_f32(f32) => serialize_f32,
+        visit_f64(f64) => serialize_f64,
+        visit_char(char) => serialize_char,
+        visit_str(&str) => serialize_str,
+        visit_bytes(&[u8]) => serialize_bytes,
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> std::result::Result<S::Ok, E> {
+        self.visit_str(&v)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> std::result::Result<S::Ok, E> {
+        self.visit_bytes(&v)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<S::Ok, E> {
+        self.serializer
+            .serialize_unit()
+            .map_err(|e| keep(self.error, e))
+    }
+
+    fn visit_none<E: de::Error>(self) -> std::result::Result<S::Ok, E> {
+        self.serializer
+            .serialize_none()
+            .map_err(|e| keep(self.error, e))
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, d: D) -> std::result::Result<S::Ok, D::Error> {
+        let transcoder = Transcoder::new(d);
+        let result = self.serializer.serialize_some(&transcoder);
+        transcoder.forward(self.error, result)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        d: D,
+    ) -> std::result::Result<S::Ok, D::Error> {
+        let transcoder = Transcoder::new(d);
+        let result = self
+            .serializer
+            .serialize_newtype_struct("<unknown>", &transcoder);
+        transcoder.forward(self.error, result)
+    }
+
+    fn visit_seq<V: SeqAccess<'de>>(self, mut v: V) -> std::result::Result<S::Ok, V::Error> {
+        let error = self.error;
+        let mut s = self
+            .serializer
+            .serialize_seq(v.size_hint())
+            .map_err(|e| keep(error, e))?;
+        while let Some(()) = v.next_element_seed(ElementSeed {
+            serializer: &mut s,
+            error,
+        })? {}
+        s.end().map_err(|e| keep(error, e))
+    }
+
+    fn visit_map<V: MapAccess<'de>>(self, mut v: V) -> std::result::Result<S::Ok, V::Error> {
+        let error = self.error;
+        let mut s = self
+            .serializer
+            .serialize_map(v.size_hint())
+            .map_err(|e| keep(error, e))?;
+        while let Some(()) = v.next_key_seed(EntrySeed {
+            serializer: &mut s,
+            error,
+            key: true,
+        })? {
+            v.next_value_seed(EntrySeed {
+                serializer: &mut s,
+                error,
+                key: false,
+            })?;
+        }
+        s.end().map_err(|e| keep(error, e))
+    }
+}
+
+struct ElementSeed<'a, S: SerializeSeq> {
+    serializer: &'a mut S,
+    error: &'a Cell<Option<S::Error>>,
+}
+
+impl<'de, 'a, S: SerializeSeq> DeserializeSeed<'de> for ElementSeed<'a, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> std::result::Result<(), D::Error> {
+        let transcoder = Transcoder::new(d);
+        let result = self.serializer.serialize_element(&transcoder);
+        transcoder.forward(self.error, result)
+    }
+}
+
+struct EntrySeed<'a, S: SerializeMap> {
+    serializer: &'a mut S,
+    error: &'a Cell<Option<S::Error>>,
+    key: bool,
+}
+
+impl<'de, 'a, S: SerializeMap> DeserializeSeed<'de> for EntrySeed<'a, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> std::result::Result<(), D::Error> {
+        let transcoder = Transcoder::new(d);
+        let result = match self.key {
+            true => self.serializer.serialize_key(&transcoder),
+            false => self.serializer.serialize_value(&transcoder),
+        };
+        transcoder.forward(self.error, result)
+    }
+}